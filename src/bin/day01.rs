@@ -1,125 +1,927 @@
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::fmt::Display;
 use std::fs::File;
-use std::io::Read;
+use std::io::{self, BufWriter, Read, Write};
+use std::ops::Range;
 use std::time::Instant;
 
-#[inline(always)]
-fn parse_u16(bytes: &[u8]) -> u16 {
-    let mut n = 0u16;
-    for b in bytes {
-        n = n * 10 + (b - b'0') as u16;
-    }
-    n
-}
+use advent_of_code::utils::LineIterator;
+use advent_of_code::utils::rank::PrefixRank;
+use advent_of_code::utils::rng::XorShift64;
 
-#[derive(Eq, PartialEq)]
+/// `L<n>` and `R<n>` turn the dial by `n` clicks, `S<n>` sets it straight to position `n`
+/// (taken modulo the dial size) without clicking through anything in between.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum Command {
-    Left(u16),
-    Right(u16),
+    Left(u64),
+    Right(u64),
+    Set(u64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ParseErrorKind {
+    UnknownCommand(u8),
+    MissingCount,
+    InvalidCount,
+    Overflow,
 }
 
+/// Where and why the command list couldn't be parsed, lines and columns start at 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ParseError {
+    line: usize,
+    column: usize,
+    kind: ParseErrorKind,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match self.kind {
+            ParseErrorKind::UnknownCommand(c) => {
+                write!(f, "unknown command {:?}", c as char)
+            }
+            ParseErrorKind::MissingCount => write!(f, "missing step count"),
+            ParseErrorKind::InvalidCount => write!(f, "step count isn't a number"),
+            ParseErrorKind::Overflow => write!(f, "step count doesn't fit in 64 bits"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
 impl TryFrom<&[u8]> for Command {
-    type Error = ();
+    type Error = ParseErrorKind;
 
-    fn try_from(mut line: &[u8]) -> Result<Command, Self::Error> {
-        if line.ends_with(b"\r") {
-            line = &line[..line.len() - 1];
+    fn try_from(token: &[u8]) -> Result<Command, Self::Error> {
+        let (&cmd, digits) = token.split_first().ok_or(ParseErrorKind::MissingCount)?;
+        if !matches!(cmd, b'L' | b'R' | b'S') {
+            return Err(ParseErrorKind::UnknownCommand(cmd));
         }
-        if line.len() < 2 {
-            return Err(());
+        if digits.is_empty() {
+            return Err(ParseErrorKind::MissingCount);
+        }
+        let mut num = 0u64;
+        for &b in digits {
+            if !b.is_ascii_digit() {
+                return Err(ParseErrorKind::InvalidCount);
+            }
+            num = num
+                .checked_mul(10)
+                .and_then(|n| n.checked_add((b - b'0') as u64))
+                .ok_or(ParseErrorKind::Overflow)?;
+        }
+        Ok(match cmd {
+            b'L' => Command::Left(num),
+            b'R' => Command::Right(num),
+            _ => Command::Set(num),
+        })
+    }
+}
+
+impl Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Command::Left(num) => write!(f, "L{num}"),
+            Command::Right(num) => write!(f, "R{num}"),
+            Command::Set(num) => write!(f, "S{num}"),
         }
-        let cmd = line[0];
-        let num = parse_u16(&line[1..]);
-        match cmd {
-            b'L' => Ok(Command::Left(num)),
-            b'R' => Ok(Command::Right(num)),
-            _ => Err(()),
+    }
+}
+
+/// Commands are separated by newlines, spaces or commas, `#` comments out the rest of
+/// the line. The first bad command stops the parsing.
+fn parse(input: &[u8]) -> Result<Vec<Command>, ParseError> {
+    let mut commands = Vec::new();
+    parse_with(input, |cmd| commands.push(cmd))?;
+    Ok(commands)
+}
+
+// hands every command to `f` as soon as it's read, for the callers that don't want a
+// `Vec<Command>` in between
+fn parse_with(input: &[u8], mut f: impl FnMut(Command)) -> Result<(), ParseError> {
+    for (line_idx, line) in LineIterator::new(input).enumerate() {
+        let code = match line.iter().position(|&b| b == b'#') {
+            Some(pos) => &line[..pos],
+            None => line,
+        };
+        for token in code
+            .split(|&b| b.is_ascii_whitespace() || b == b',')
+            .filter(|token| !token.is_empty())
+        {
+            let command = Command::try_from(token).map_err(|kind| ParseError {
+                line: line_idx + 1,
+                column: token.as_ptr() as usize - line.as_ptr() as usize + 1,
+                kind,
+            })?;
+            f(command);
         }
     }
+    Ok(())
 }
 
 #[derive(Debug)]
 struct Dial {
-    dial: u8,
-    count_roll: bool,
+    size: u64,
+    dial: u64,
+}
+
+/// Geometry of the lock, defaults to the puzzle's 100 clicks starting on 50.
+#[derive(Clone, Copy, Debug)]
+struct DialBuilder {
+    size: u64,
+    start: u64,
+}
+
+impl Default for DialBuilder {
+    fn default() -> Self {
+        Self {
+            size: 100,
+            start: 50,
+        }
+    }
+}
+
+impl DialBuilder {
+    /// Number of clicks in a full turn.
+    pub fn size(mut self, size: u64) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn start(mut self, start: u64) -> Self {
+        self.start = start;
+        self
+    }
+
+    pub fn build(self) -> Dial {
+        assert!(self.size > 0, "a dial needs at least one position");
+        assert!(self.start < self.size, "start position outside of the dial");
+        Dial {
+            size: self.size,
+            dial: self.start,
+        }
+    }
+}
+
+/// What a single command did to the dial, this is all a [`Policy`] gets to see.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Rotation {
+    from: u64,
+    to: u64,
+    command: Command,
+    size: u64,
+}
+
+impl Rotation {
+    #[inline(always)]
+    fn clicks(&self) -> u64 {
+        match self.command {
+            Command::Left(num) | Command::Right(num) => num,
+            Command::Set(_) => 0,
+        }
+    }
+
+    #[inline(always)]
+    fn left(&self) -> bool {
+        matches!(self.command, Command::Left(_))
+    }
+
+    /// Number of clicks that leave the dial on `target`. The starting position doesn't
+    /// count, the final one does.
+    #[inline(always)]
+    fn passes(&self, target: u64) -> u64 {
+        // clicks until the first time we reach the target
+        let first = if self.left() {
+            sub_mod(self.from, target, self.size)
+        } else {
+            sub_mod(target, self.from, self.size)
+        };
+        let first = if first == 0 { self.size } else { first };
+        let clicks = self.clicks();
+        if clicks < first {
+            0
+        } else {
+            (clicks - first) / self.size + 1
+        }
+    }
+}
+
+// `(a - b) mod size` for `a, b < size`
+#[inline(always)]
+fn sub_mod(a: u64, b: u64, size: u64) -> u64 {
+    if a >= b { a - b } else { a + (size - b) }
 }
 
 impl Dial {
-    const NB_CLICK: u8 = 100;
+    pub fn builder() -> DialBuilder {
+        DialBuilder::default()
+    }
 
     #[inline(always)]
-    pub fn new(count_roll: bool) -> Self {
-        Self {
-            dial: 50,
-            count_roll,
+    fn apply(&mut self, cmd: &Command) -> Rotation {
+        let from = self.dial;
+        // written so nothing overflows even when the size is close to u64::MAX
+        self.dial = match *cmd {
+            Command::Left(num) => sub_mod(from, num % self.size, self.size),
+            Command::Right(num) => sub_mod(from, self.size - num % self.size, self.size),
+            Command::Set(num) => num % self.size,
+        };
+        Rotation {
+            from,
+            to: self.dial,
+            command: *cmd,
+            size: self.size,
         }
     }
+}
+
+/// What gets counted while the dial turns, part 1 and part 2 are just two of them.
+trait Policy {
+    fn observe(&mut self, rotation: &Rotation);
+}
 
+// run two policies in the same pass
+impl<A: Policy, B: Policy> Policy for (A, B) {
     #[inline(always)]
-    fn go_left(&mut self, num: u16) -> Option<u16> {
-        self.rotate(num, true)
+    fn observe(&mut self, rotation: &Rotation) {
+        self.0.observe(rotation);
+        self.1.observe(rotation);
+    }
+}
+
+fn target_set(targets: impl IntoIterator<Item = u64>) -> Vec<u64> {
+    let mut targets: Vec<_> = targets.into_iter().collect();
+    targets.sort_unstable();
+    targets.dedup();
+    targets
+}
+
+/// Rotations that end on one of the targets.
+#[derive(Debug)]
+struct Landings {
+    targets: Vec<u64>,
+    count: u64,
+}
+
+impl Landings {
+    pub fn new(targets: impl IntoIterator<Item = u64>) -> Self {
+        Self {
+            targets: target_set(targets),
+            count: 0,
+        }
     }
+}
 
+impl Policy for Landings {
     #[inline(always)]
-    fn go_right(&mut self, num: u16) -> Option<u16> {
-        self.rotate(num, false)
+    fn observe(&mut self, rotation: &Rotation) {
+        self.count += self.targets.contains(&rotation.to) as u64;
     }
+}
+
+/// Clicks that leave the dial on one of the targets, wherever in a rotation they happen.
+#[derive(Debug)]
+struct Passes {
+    targets: Vec<u64>,
+    count: u64,
+}
 
+impl Passes {
+    pub fn new(targets: impl IntoIterator<Item = u64>) -> Self {
+        Self {
+            targets: target_set(targets),
+            count: 0,
+        }
+    }
+}
+
+impl Policy for Passes {
     #[inline(always)]
-    fn rotate(&mut self, step: u16, left: bool) -> Option<u16> {
-        let clicks = Self::NB_CLICK as u16;
-        let mut rolls = step / clicks;
-        let step = (step % clicks) as u8;
-
-        let old = self.dial;
-        self.dial = if left {
-            (self.dial + Self::NB_CLICK - step) % Self::NB_CLICK
+    fn observe(&mut self, rotation: &Rotation) {
+        self.count += self
+            .targets
+            .iter()
+            .map(|&target| rotation.passes(target))
+            .sum::<u64>();
+    }
+}
+
+/// How many clicks left the dial on each position. Full turns are kept apart and the
+/// partial ones go in a difference array, so a rotation costs O(1) whatever its length.
+#[derive(Debug)]
+struct Histogram {
+    full_turns: u64,
+    diff: Vec<i64>,
+}
+
+impl Histogram {
+    pub fn new(size: u64) -> Self {
+        Self {
+            full_turns: 0,
+            diff: vec![0; size as usize + 1],
+        }
+    }
+
+    pub fn visits(&self) -> Vec<u64> {
+        let mut acc = 0;
+        self.diff[..self.diff.len() - 1]
+            .iter()
+            .map(|d| {
+                acc += d;
+                self.full_turns + acc as u64
+            })
+            .collect()
+    }
+}
+
+impl Policy for Histogram {
+    fn observe(&mut self, rotation: &Rotation) {
+        let size = rotation.size;
+        let clicks = rotation.clicks();
+        self.full_turns += clicks / size;
+        let partial = clicks % size;
+        if partial == 0 {
+            return;
+        }
+        // positions reached by the partial turn, as a range that may wrap around
+        let start = if rotation.left() {
+            rotation.to
         } else {
-            (self.dial + step) % Self::NB_CLICK
+            (rotation.from + 1) % size
+        };
+        add_arc(&mut self.diff, start, partial);
+    }
+}
+
+// +1 on the `len` positions from `start` in a difference array over the whole dial
+// (one slot longer than the dial), wrapping around past the end
+#[inline(always)]
+fn add_arc(diff: &mut [i64], start: u64, len: u64) {
+    let size = (diff.len() - 1) as u64;
+    if len <= size - start {
+        diff[start as usize] += 1;
+        diff[(start + len) as usize] -= 1;
+    } else {
+        diff[start as usize] += 1;
+        diff[size as usize] -= 1;
+        diff[0] += 1;
+        diff[(len - (size - start)) as usize] -= 1;
+    }
+}
+
+/// Commands turning the other way than the previous one, empty rotations are ignored.
+#[derive(Debug, Default)]
+struct DirectionChanges {
+    last: Option<bool>,
+    count: u64,
+}
+
+impl Policy for DirectionChanges {
+    #[inline(always)]
+    fn observe(&mut self, rotation: &Rotation) {
+        if rotation.clicks() == 0 {
+            return;
+        }
+        let left = rotation.left();
+        if self.last.is_some_and(|last| last != left) {
+            self.count += 1;
+        }
+        self.last = Some(left);
+    }
+}
+
+/// Part 1 and part 2 answers for every starting position at once, from a single run.
+#[derive(Debug)]
+struct Sweep {
+    landings: Vec<u64>,
+    passes: Vec<i64>,
+    // counts shared by every start: full turns, and everything after an `S` command
+    shared_landings: u64,
+    shared_passes: u64,
+    merged: bool,
+}
+
+/// Answers for one starting position, as listed by [`Sweep::rows`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SweepRow {
+    start: u64,
+    part1: u64,
+    part2: u64,
+}
+
+impl Sweep {
+    pub fn new(commands: &[Command], size: u64) -> Self {
+        let mut dial = Dial::builder().size(size).start(0).build();
+        let mut sweep = Self {
+            landings: vec![0; size as usize],
+            passes: vec![0; size as usize + 1],
+            shared_landings: 0,
+            shared_passes: 0,
+            merged: false,
         };
+        for cmd in commands {
+            sweep.observe(&dial.apply(cmd));
+        }
+        sweep
+    }
+
+    // until the first `S` command a dial started on `s` stays `s` clicks ahead of this
+    // one, after it every start agrees
+    fn observe(&mut self, rotation: &Rotation) {
+        let size = rotation.size;
+        self.merged |= matches!(rotation.command, Command::Set(_));
+        if self.merged {
+            self.shared_landings += (rotation.to == 0) as u64;
+            self.shared_passes += rotation.passes(0);
+            return;
+        }
+        // the start `s` such that `s + to` is zero
+        self.landings[((size - rotation.to) % size) as usize] += 1;
+        let clicks = rotation.clicks();
+        self.shared_passes += clicks / size;
+        let partial = clicks % size;
+        if partial > 0 {
+            // positions the partial turn must start from to reach zero, then shifted back
+            // by where the reference dial started
+            let first = if rotation.left() { 1 } else { size - partial };
+            add_arc(
+                &mut self.passes,
+                sub_mod(first, rotation.from, size),
+                partial,
+            );
+        }
+    }
+
+    pub fn rows(&self) -> Vec<SweepRow> {
+        let mut acc = 0;
+        self.landings
+            .iter()
+            .zip(&self.passes)
+            .enumerate()
+            .map(|(start, (landings, passes))| {
+                acc += passes;
+                SweepRow {
+                    start: start as u64,
+                    part1: self.shared_landings + landings,
+                    part2: self.shared_passes + acc as u64,
+                }
+            })
+            .collect()
+    }
+
+    pub fn write_csv(&self, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "start,part1,part2")?;
+        for row in self.rows() {
+            writeln!(out, "{},{},{}", row.start, row.part1, row.part2)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Sweep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:>6} {:>8} {:>8}", "start", "part1", "part2")?;
+        for row in self.rows() {
+            writeln!(f, "{:>6} {:>8} {:>8}", row.start, row.part1, row.part2)?;
+        }
+        Ok(())
+    }
+}
+
+/// One command as seen by a [`Trace`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TraceStep {
+    command: Command,
+    start: u64,
+    end: u64,
+    zero_passes: u64,
+    // zero passes of this command and all the ones before
+    total: u64,
+}
+
+/// Opt-in record of every command, to find the exact step where two implementations
+/// (or two geometries) start to disagree.
+#[derive(Debug)]
+struct Trace {
+    // position before the first command, unknown for an empty trace read from CSV
+    start: Option<u64>,
+    steps: Vec<TraceStep>,
+}
 
-        let crossed_zero = (left && old < self.dial && old != 0)
-            || (!left && old > self.dial && old != 0)
-            || self.dial == 0;
+impl Policy for Trace {
+    fn observe(&mut self, rotation: &Rotation) {
+        let zero_passes = rotation.passes(0);
+        let total = self.steps.last().map_or(0, |step| step.total) + zero_passes;
+        self.steps.push(TraceStep {
+            command: rotation.command,
+            start: rotation.from,
+            end: rotation.to,
+            zero_passes,
+            total,
+        });
+    }
+}
+
+impl Trace {
+    pub fn new(start: u64) -> Self {
+        Self {
+            start: Some(start),
+            steps: Vec::new(),
+        }
+    }
+
+    /// `(position, zero passes so far)` once the first `k` commands ran,
+    /// `k = 0` being the starting state.
+    pub fn state_after(&self, k: usize) -> Option<(u64, u64)> {
+        match k {
+            0 => self.start.map(|start| (start, 0)),
+            _ => self.steps.get(k - 1).map(|step| (step.end, step.total)),
+        }
+    }
 
-        if self.count_roll {
-            if crossed_zero {
-                rolls += 1;
+    /// Index of the first command recorded differently in `other`, `None` if both
+    /// traces are identical.
+    pub fn first_divergence(&self, other: &Trace) -> Option<usize> {
+        self.steps
+            .iter()
+            .zip(&other.steps)
+            .position(|(a, b)| a != b)
+            .or_else(|| {
+                (self.steps.len() != other.steps.len())
+                    .then_some(self.steps.len().min(other.steps.len()))
+            })
+    }
+
+    /// Reads back what [`Trace::write_csv`] wrote, `None` on a malformed row.
+    pub fn from_csv(csv: &str) -> Option<Self> {
+        let mut steps = Vec::new();
+        for line in csv.lines().skip(1).filter(|line| !line.is_empty()) {
+            let mut fields = line.split(',').skip(1);
+            let command = Command::try_from(fields.next()?.as_bytes()).ok()?;
+            let mut num = || fields.next()?.parse().ok();
+            steps.push(TraceStep {
+                command,
+                start: num()?,
+                end: num()?,
+                zero_passes: num()?,
+                total: num()?,
+            });
+        }
+        Some(Self {
+            start: steps.first().map(|step| step.start),
+            steps,
+        })
+    }
+
+    pub fn write_csv(&self, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "step,command,start,end,zero_passes,total")?;
+        for (i, step) in self.steps.iter().enumerate() {
+            writeln!(
+                out,
+                "{},{},{},{},{},{}",
+                i + 1,
+                step.command,
+                step.start,
+                step.end,
+                step.zero_passes,
+                step.total
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Trace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:>6} {:>8} {:>6} {:>6} {:>6} {:>8}",
+            "step", "command", "start", "end", "zero", "total"
+        )?;
+        for (i, step) in self.steps.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>6} {:>8} {:>6} {:>6} {:>6} {:>8}",
+                i + 1,
+                step.command.to_string(),
+                step.start,
+                step.end,
+                step.zero_passes,
+                step.total
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Zero landings and passes of any slice of the commands from any starting position,
+/// in O(log n) each.
+#[derive(Debug)]
+struct ZeroIndex {
+    size: u64,
+    // offset[k]: displacement of the first k commands, `S` commands count as 0
+    offset: Vec<u64>,
+    // positions `k` of `offset` for each offset value, sorted
+    offset_positions: HashMap<u64, Vec<usize>>,
+    // full turns of the first k commands
+    full_turns: Vec<u64>,
+    // arcs of starting positions (shifted by the offset) where a partial turn passes zero,
+    // as their first and last position
+    arc_starts: PrefixRank,
+    arc_ends: PrefixRank,
+    // index of each `S` command and the position it sets
+    sets: Vec<(usize, u64)>,
+    // (landings, passes) of the first k commands in a simulation starting on 0, only
+    // differences after an `S` command are meaningful
+    simulated: Vec<(u64, u64)>,
+}
+
+impl ZeroIndex {
+    pub fn new(commands: &[Command], size: u64) -> Self {
+        assert!(size > 0, "a dial needs at least one position");
+        let len = commands.len();
+        let mut offset = Vec::with_capacity(len + 1);
+        let mut full_turns = Vec::with_capacity(len + 1);
+        let mut arcs = Vec::new();
+        let mut sets = Vec::new();
+        let (mut current, mut turns) = (0, 0);
+        for (k, &cmd) in commands.iter().enumerate() {
+            offset.push(current);
+            full_turns.push(turns);
+            // positions the partial turn starts from to pass zero: `1..=rem` going left,
+            // `size - rem..size` going right
+            let (rem, first) = match cmd {
+                Command::Left(num) => (num % size, 1),
+                Command::Right(num) => (num % size, size - num % size),
+                Command::Set(num) => {
+                    sets.push((k, num % size));
+                    (0, 0)
+                }
+            };
+            if let Command::Left(num) | Command::Right(num) = cmd {
+                turns += num / size;
+            }
+            if rem > 0 {
+                let start = sub_mod(first % size, current, size);
+                if rem <= size - start {
+                    arcs.push((k, start, start + (rem - 1)));
+                } else {
+                    arcs.push((k, start, size - 1));
+                    arcs.push((k, 0, rem - (size - start) - 1));
+                }
+            }
+            current = match cmd {
+                Command::Left(num) => sub_mod(current, num % size, size),
+                Command::Right(num) => sub_mod(current, size - num % size, size),
+                Command::Set(_) => current,
+            };
+        }
+        offset.push(current);
+        full_turns.push(turns);
+
+        let mut offset_positions: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (k, &value) in offset.iter().enumerate() {
+            offset_positions.entry(value).or_default().push(k);
+        }
+
+        let mut simulated = vec![(0, 0)];
+        let mut dial = Dial::builder().size(size).start(0).build();
+        for cmd in commands {
+            let rotation = dial.apply(cmd);
+            let (landings, passes) = simulated[simulated.len() - 1];
+            simulated.push((
+                landings + (rotation.to == 0) as u64,
+                passes + rotation.passes(0),
+            ));
+        }
+
+        Self {
+            size,
+            offset,
+            offset_positions,
+            full_turns,
+            arc_starts: PrefixRank::new(len, arcs.iter().map(|&(k, start, _)| (k, start))),
+            arc_ends: PrefixRank::new(len, arcs.iter().map(|&(k, _, end)| (k, end))),
+            sets,
+            simulated,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.offset.len() - 1
+    }
+
+    // first `S` command of the range, everything before it follows the start position
+    fn first_set(&self, range: &Range<usize>) -> Option<usize> {
+        assert!(range.start <= range.end && range.end <= self.len());
+        let idx = self.sets.partition_point(|&(k, _)| k < range.start);
+        self.sets
+            .get(idx)
+            .map(|&(k, _)| k)
+            .filter(|&k| k < range.end)
+    }
+
+    // counts of the range from its first `S` command onward
+    fn after_set(&self, set: usize, end: usize) -> (u64, u64) {
+        let (landings, passes) = self.simulated[end];
+        let (before_landings, before_passes) = self.simulated[set];
+        (landings - before_landings, passes - before_passes)
+    }
+
+    /// Commands of `range` that end on zero when the dial is on `start` before the first one.
+    pub fn landings(&self, range: Range<usize>, start: u64) -> u64 {
+        assert!(start < self.size, "start position outside of the dial");
+        let (free, rest) = match self.first_set(&range) {
+            Some(set) => (set, self.after_set(set, range.end).0),
+            None => (range.end, 0),
+        };
+        // command k ends on zero when offset[k + 1] - offset[start] == -start
+        let target = sub_mod(self.offset[range.start], start, self.size);
+        let hits = self.offset_positions.get(&target).map_or(0, |positions| {
+            positions.partition_point(|&k| k <= free)
+                - positions.partition_point(|&k| k <= range.start)
+        });
+        hits as u64 + rest
+    }
+
+    /// Clicks of the commands of `range` that leave the dial on zero when it is on `start`
+    /// before the first one.
+    pub fn passes(&self, range: Range<usize>, start: u64) -> u64 {
+        assert!(start < self.size, "start position outside of the dial");
+        let (free, rest) = match self.first_set(&range) {
+            Some(set) => (set, self.after_set(set, range.end).1),
+            None => (range.end, 0),
+        };
+        let shifted = sub_mod(start, self.offset[range.start], self.size);
+        let arcs = self.arc_starts.count_le(range.start..free, shifted)
+            - self.arc_ends.count_lt(range.start..free, shifted);
+        self.full_turns[free] - self.full_turns[range.start] + arcs + rest
+    }
+
+    /// Position of the dial after the commands of `range`, starting from `start`.
+    pub fn position(&self, range: Range<usize>, start: u64) -> u64 {
+        assert!(start < self.size, "start position outside of the dial");
+        let (from, base) = match self.first_set(&range) {
+            Some(_) => {
+                let idx = self.sets.partition_point(|&(k, _)| k < range.end);
+                let (set, position) = self.sets[idx - 1];
+                (set + 1, position)
+            }
+            None => (range.start, start),
+        };
+        let moved = sub_mod(self.offset[range.end], self.offset[from], self.size);
+        sub_mod(base, self.size - moved, self.size)
+    }
+}
+
+/// Branch-free solver for the two answers over the signed partial turn of every command,
+/// what the puzzle parts use. Dials need fewer than 2^62 positions.
+#[derive(Debug)]
+struct Batch {
+    size: i64,
+    turns: u64,
+    // partial turn of each command, in `-size + 1..size`, 0 for `S` commands
+    deltas: Vec<i64>,
+    // index of each `S` command and the position it sets
+    sets: Vec<(usize, i64)>,
+}
+
+// commands per block, small enough for the positions to stay in L1
+const BLOCK: usize = 1024;
+
+impl Batch {
+    /// Dials must have fewer positions than this.
+    pub const MAX_SIZE: u64 = 1 << 62;
+
+    fn with_size(size: u64) -> Self {
+        assert!(size > 0, "a dial needs at least one position");
+        assert!(
+            size < Self::MAX_SIZE,
+            "dial too large for the batched solver"
+        );
+        Self {
+            size: size as i64,
+            turns: 0,
+            deltas: Vec::new(),
+            sets: Vec::new(),
+        }
+    }
+
+    /// Parses `input` with the same grammar as [`parse`] straight into the deltas.
+    pub fn parse(input: &[u8], size: u64) -> Result<Self, ParseError> {
+        let mut batch = Self::with_size(size);
+        // about 5 bytes per command in the puzzle input
+        batch.deltas.reserve(input.len() / 5);
+        parse_with(input, |cmd| batch.push(cmd))?;
+        Ok(batch)
+    }
+
+    #[inline(always)]
+    fn push(&mut self, cmd: Command) {
+        let size = self.size as u64;
+        let (num, sign) = match cmd {
+            Command::Left(num) => (num, -1),
+            Command::Right(num) => (num, 1),
+            Command::Set(num) => {
+                self.sets.push((self.deltas.len(), (num % size) as i64));
+                (0, 0)
+            }
+        };
+        // one division per command, the remainder comes from the quotient
+        let full = num / size;
+        self.turns += full;
+        self.deltas.push(sign * (num - full * size) as i64);
+    }
+
+    /// (part 1, part 2) with the dial starting on `start`.
+    pub fn solve(&self, start: u64) -> (u64, u64) {
+        assert!(
+            start < self.size as u64,
+            "start position outside of the dial"
+        );
+        let size = self.size;
+        let mut positions = [0; BLOCK + 1];
+        let (mut landings, mut passes) = (0, self.turns);
+        let mut pos = start as i64;
+        let mut from = 0;
+        // runs of commands between `S` commands, each `S` command only moves the dial
+        let sets = self.sets.iter().map(|&(k, value)| (k, Some(value)));
+        for (end, value) in sets.chain([(self.deltas.len(), None)]) {
+            for deltas in self.deltas[from..end].chunks(BLOCK) {
+                positions[0] = pos;
+                for (i, &delta) in deltas.iter().enumerate() {
+                    let next = pos + delta;
+                    // back into `0..size` without branching: add `size` if negative, then
+                    // take it away if still too large
+                    let next = next + (size & (next >> 63));
+                    pos = next - (size & !((next - size) >> 63));
+                    positions[i + 1] = pos;
+                }
+                for (window, &delta) in positions[..=deltas.len()].windows(2).zip(deltas) {
+                    let (from, to) = (window[0], window[1]);
+                    let end = from + delta;
+                    landings += (to == 0) as u64;
+                    // a partial turn reaches zero going right when it goes past the last
+                    // position, going left when it starts above zero and gets down to it
+                    let right = end >= size;
+                    let left = (from > 0) & (end <= 0);
+                    passes += (right | left) as u64;
+                }
             }
-            Some(rolls)
+            if let Some(value) = value {
+                pos = value;
+                landings += (pos == 0) as u64;
+            }
+            from = end + 1;
+        }
+        (landings, passes)
+    }
+}
+
+fn run<P: Policy>(commands: &[Command], geometry: DialBuilder, mut policy: P) -> P {
+    let mut dial = geometry.build();
+    for cmd in commands {
+        policy.observe(&dial.apply(cmd));
+    }
+    policy
+}
+
+/// Input of the two parts, parsed for [`Batch`] unless the dial is too large for it.
+enum Parts {
+    Batch(Batch),
+    Dial(Vec<Command>),
+}
+
+impl Parts {
+    pub fn parse(input: &[u8], size: u64) -> Result<Self, ParseError> {
+        if size < Batch::MAX_SIZE {
+            Batch::parse(input, size).map(Parts::Batch)
         } else {
-            (self.dial == 0).then_some(rolls)
+            parse(input).map(Parts::Dial)
         }
     }
 }
 
-fn solve_part1(input: &[u8]) -> u16 {
-    let mut dial = Dial::new(false);
+fn solve_part1(parts: &Parts, geometry: DialBuilder) -> u64 {
     let now = Instant::now();
-    let answer = input
-        .split(|b| *b == b'\n')
-        .filter_map(|line| Command::try_from(line).ok())
-        .filter_map(|cmd| match cmd {
-            Command::Left(num) => dial.go_left(num),
-            Command::Right(num) => dial.go_right(num),
-        })
-        .count() as u16;
+    let answer = match parts {
+        Parts::Batch(batch) => batch.solve(geometry.start).0,
+        Parts::Dial(commands) => run(commands, geometry, Landings::new([0])).count,
+    };
 
     let elapsed = now.elapsed();
     println!("took: {:?}", elapsed);
     answer
 }
 
-fn solve_part2(input: &[u8]) -> u16 {
-    let mut dial = Dial::new(true);
+fn solve_part2(parts: &Parts, geometry: DialBuilder) -> u64 {
     let now = Instant::now();
-    let answer = input
-        .split(|b| *b == b'\n')
-        .filter_map(|line| Command::try_from(line).ok())
-        .filter_map(|cmd| match cmd {
-            Command::Left(num) => dial.go_left(num),
-            Command::Right(num) => dial.go_right(num),
-        })
-        .sum::<u16>();
+    let answer = match parts {
+        Parts::Batch(batch) => batch.solve(geometry.start).1,
+        Parts::Dial(commands) => run(commands, geometry, Passes::new([0])).count,
+    };
 
     let elapsed = now.elapsed();
     println!("took: {:?}", elapsed);
@@ -133,13 +935,207 @@ fn main() {
         .read_to_end(&mut buf)
         .unwrap();
 
-    println!("Part 1: {}", solve_part1(&buf));
-    println!("Part 2: {}", solve_part2(&buf));
+    // `--size N` and `--start N` to try locks other than the puzzle's,
+    // `--histogram` and `--changes` for some more stats about the input,
+    // `--trace FILE` to dump every command as CSV (`-` prints a table instead),
+    // `--at K` to see the state after K commands and `--against FILE` to find
+    // where a CSV trace from another implementation disagrees with this one,
+    // `--between I J` for the zero counts of commands I to J (excluded) alone,
+    // `--sweep FILE` for both answers from every starting position (`-` prints a table),
+    // `--bench N` to time the batched solver against `Dial` on N random commands, parsing
+    // included
+    let mut geometry = Dial::builder();
+    let (mut histogram, mut changes) = (false, false);
+    let (mut trace_out, mut at, mut against) = (None, None, None);
+    let (mut between, mut sweep_out, mut bench) = (None, None, None);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().expect("missing value after option");
+        match arg.as_str() {
+            "--size" => geometry = geometry.size(value().parse().unwrap()),
+            "--start" => geometry = geometry.start(value().parse().unwrap()),
+            "--histogram" => histogram = true,
+            "--changes" => changes = true,
+            "--trace" => trace_out = Some(value()),
+            "--at" => at = Some(value().parse().unwrap()),
+            "--against" => against = Some(value()),
+            "--sweep" => sweep_out = Some(value()),
+            "--bench" => bench = Some(value().parse().unwrap()),
+            "--between" => {
+                let from: usize = value().parse().unwrap();
+                between = Some(from..value().parse().unwrap());
+            }
+            _ => panic!(
+                "usage: day01 [--size N] [--start N] [--histogram] [--changes] \
+                 [--trace FILE] [--at K] [--against FILE] [--between I J] [--sweep FILE] [--bench N]"
+            ),
+        }
+    }
+
+    let parts = match Parts::parse(&buf, geometry.size) {
+        Ok(parts) => parts,
+        Err(err) => {
+            eprintln!("inputs/day01.txt: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    println!("Part 1: {}", solve_part1(&parts, geometry));
+    println!("Part 2: {}", solve_part2(&parts, geometry));
+    // the options below go through `Dial`, the input is only parsed again if one is given
+    let commands = OnceCell::new();
+    let commands = || commands.get_or_init(|| parse(&buf).unwrap()).as_slice();
+    if let Some(len) = bench {
+        let mut rng = XorShift64::new(len);
+        let generated: String = (0..len)
+            .map(|_| match rng.bool() {
+                true => format!("L{}\n", rng.below(1000)),
+                false => format!("R{}\n", rng.below(1000)),
+            })
+            .collect();
+        // parsing included, it's most of the time for both
+        let now = Instant::now();
+        let commands = parse(generated.as_bytes()).unwrap();
+        let parsed = now.elapsed();
+        let policy = (Landings::new([0]), Passes::new([0]));
+        let (landings, passes) = run(&commands, geometry, policy);
+        println!(
+            "Dial: {:?} in {:?} (parsing took {parsed:?})",
+            (landings.count, passes.count),
+            now.elapsed()
+        );
+        if geometry.size < Batch::MAX_SIZE {
+            let now = Instant::now();
+            let batch = Batch::parse(generated.as_bytes(), geometry.size).unwrap();
+            let parsed = now.elapsed();
+            let answers = batch.solve(geometry.start);
+            println!(
+                "Batch: {answers:?} in {:?} (parsing took {parsed:?})",
+                now.elapsed()
+            );
+        } else {
+            println!("Batch: dial too large");
+        }
+    }
+    if histogram {
+        let visits = run(commands(), geometry, Histogram::new(geometry.size)).visits();
+        for (position, count) in visits.iter().enumerate() {
+            println!("{position}: {count}");
+        }
+    }
+    if changes {
+        let changes = run(commands(), geometry, DirectionChanges::default());
+        println!("Direction changes: {}", changes.count);
+    }
+    if let Some(path) = sweep_out {
+        let sweep = Sweep::new(commands(), geometry.size);
+        match path.as_str() {
+            "-" => print!("{sweep}"),
+            path => sweep
+                .write_csv(BufWriter::new(File::create(path).unwrap()))
+                .unwrap(),
+        }
+    }
+    if let Some(range) = between {
+        let index = ZeroIndex::new(commands(), geometry.size);
+        let start = index.position(0..range.start, geometry.start);
+        println!(
+            "Commands {}..{} from {start}: {} landings, {} passes on zero",
+            range.start,
+            range.end,
+            index.landings(range.clone(), start),
+            index.passes(range.clone(), start)
+        );
+    }
+    if trace_out.is_some() || at.is_some() || against.is_some() {
+        let trace = run(commands(), geometry, Trace::new(geometry.start));
+        match trace_out.as_deref() {
+            Some("-") => print!("{trace}"),
+            Some(path) => trace
+                .write_csv(BufWriter::new(File::create(path).unwrap()))
+                .unwrap(),
+            None => {}
+        }
+        if let Some(k) = at {
+            match trace.state_after(k) {
+                Some((position, total)) => {
+                    println!("After {k} commands: position {position}, {total} zero passes")
+                }
+                None => println!("Only {} commands", trace.steps.len()),
+            }
+        }
+        if let Some(path) = against {
+            let csv = std::fs::read_to_string(path).unwrap();
+            let other = Trace::from_csv(&csv).expect("malformed trace");
+            match trace.first_divergence(&other) {
+                Some(k) => println!(
+                    "First divergence at step {}: {:?} vs {:?}",
+                    k + 1,
+                    trace.steps.get(k),
+                    other.steps.get(k)
+                ),
+                None => println!("Traces are identical"),
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{solve_part1, solve_part2};
+    use crate::{
+        Batch, Command, Dial, DirectionChanges, Histogram, Landings, ParseError, ParseErrorKind,
+        Parts, Passes, Policy, Sweep, SweepRow, Trace, ZeroIndex, parse, run, solve_part1,
+        solve_part2,
+    };
+    use advent_of_code::utils::rng::XorShift64;
+
+    struct BruteForce {
+        landings: Vec<u64>,
+        visits: Vec<u64>,
+    }
+
+    // click by click reference: how many rotations end on each position and how many
+    // clicks leave the dial on each position
+    fn brute_force(size: u64, start: u64, commands: &[Command]) -> BruteForce {
+        let mut dial = start;
+        let mut landings = vec![0; size as usize];
+        let mut visits = vec![0; size as usize];
+        for &cmd in commands {
+            let (step, back) = match cmd {
+                Command::Left(num) => (num, size - 1),
+                Command::Right(num) => (num, 1),
+                Command::Set(num) => {
+                    dial = num % size;
+                    (0, 0)
+                }
+            };
+            for _ in 0..step {
+                dial = (dial + back) % size;
+                visits[dial as usize] += 1;
+            }
+            landings[dial as usize] += 1;
+        }
+        BruteForce { landings, visits }
+    }
+
+    fn simulate<P: Policy>(size: u64, start: u64, commands: &[Command], policy: P) -> P {
+        run(commands, Dial::builder().size(size).start(start), policy)
+    }
+
+    fn random_command(rng: &mut XorShift64, size: u64) -> Command {
+        match rng.below(10) {
+            0 => Command::Set(rng.below(2 * size)),
+            1..5 => Command::Left(rng.below(4 * size)),
+            _ => Command::Right(rng.below(4 * size)),
+        }
+    }
+
+    // (rotations ending on zero, passes through zero)
+    fn zero_counts(size: u64, start: u64, commands: &[Command]) -> (u64, u64) {
+        let policy = (Landings::new([0]), Passes::new([0]));
+        let (landings, passes) = simulate(size, start, commands, policy);
+        (landings.count, passes.count)
+    }
 
     #[test]
     fn test() {
@@ -147,10 +1143,257 @@ mod tests {
             "L68", "L30", "R48", "L5", "R60", "L55", "L1", "L99", "R14", "L82",
         ]
         .join("\n");
-        let buf = value.as_bytes();
-        let res1 = solve_part1(&buf);
-        let res2 = solve_part2(&buf);
+        let parts = Parts::parse(value.as_bytes(), 100).unwrap();
+        let res1 = solve_part1(&parts, Dial::builder());
+        let res2 = solve_part2(&parts, Dial::builder());
         assert_eq!(res1, 3);
         assert_eq!(res2, 6);
+
+        // too large for `Batch`, the parts go through `Dial`
+        let geometry = Dial::builder().size(u64::MAX).start(0);
+        let parts = Parts::parse(b"L1 R5 R18446744073709551615", u64::MAX).unwrap();
+        assert!(matches!(parts, Parts::Dial(_)));
+        assert_eq!(solve_part1(&parts, geometry), 0);
+        assert_eq!(solve_part2(&parts, geometry), 2);
+    }
+
+    #[test]
+    fn test_random_sizes() {
+        let mut rng = XorShift64::new(0xd1a1);
+        for _ in 0..500 {
+            let size = rng.range(1..=150);
+            let start = rng.below(size);
+            let commands: Vec<_> = (0..rng.range(1..=50))
+                .map(|_| random_command(&mut rng, size))
+                .collect();
+            let expected = brute_force(size, start, &commands);
+            assert_eq!(
+                zero_counts(size, start, &commands),
+                (expected.landings[0], expected.visits[0]),
+                "size {size}, start {start}, commands {commands:?}"
+            );
+
+            let mut targets: Vec<_> = (0..rng.range(1..=3)).map(|_| rng.below(size)).collect();
+            targets.sort_unstable();
+            targets.dedup();
+            let policy = (Landings::new(targets.clone()), Passes::new(targets.clone()));
+            let (landings, passes) = simulate(size, start, &commands, policy);
+            let sum = |counts: &[u64]| targets.iter().map(|&t| counts[t as usize]).sum::<u64>();
+            assert_eq!(landings.count, sum(&expected.landings));
+            assert_eq!(passes.count, sum(&expected.visits));
+
+            let histogram = simulate(size, start, &commands, Histogram::new(size));
+            assert_eq!(histogram.visits(), expected.visits);
+        }
+    }
+
+    #[test]
+    fn test_full_turns() {
+        // a full turn from zero passes it once, it must not also count as a landing pass
+        let commands = [Command::Left(100), Command::Right(0), Command::Right(200)];
+        assert_eq!(zero_counts(100, 0, &commands), (3, 3));
+        let expected = brute_force(100, 0, &commands);
+        assert_eq!((expected.landings[0], expected.visits[0]), (3, 3));
+    }
+
+    #[test]
+    fn test_huge_dial() {
+        let size = u64::MAX;
+        let commands = [
+            Command::Left(1),
+            Command::Left(u64::MAX - 1),
+            Command::Right(5),
+            Command::Right(u64::MAX),
+        ];
+        // ends on MAX - 1, then 0, then 5, then 5 again after exactly one turn
+        assert_eq!(zero_counts(size, 0, &commands), (1, 2));
+        assert_eq!(zero_counts(size, size - 1, &[Command::Right(1)]), (1, 1));
+        assert_eq!(zero_counts(size, 5, &[Command::Set(u64::MAX)]), (1, 0));
+    }
+
+    #[test]
+    fn test_direction_changes() {
+        let commands = parse(b"L3 R0 L2 S7 R1 R4 L1").unwrap();
+        let changes = simulate(10, 5, &commands, DirectionChanges::default());
+        assert_eq!(changes.count, 2);
+    }
+
+    #[test]
+    fn test_trace() {
+        let input = parse(b"L68\nL30\nR48\nL5\nR60\nL55\nL1\nL99\nR14\nL82").unwrap();
+        let trace = run(&input, Dial::builder(), Trace::new(50));
+        assert_eq!(trace.state_after(0), Some((50, 0)));
+        assert_eq!(trace.state_after(1), Some((82, 1)));
+        assert_eq!(trace.state_after(3), Some((0, 2)));
+        assert_eq!(trace.state_after(10), Some((32, 6)));
+        assert_eq!(trace.state_after(11), None);
+
+        let mut csv = Vec::new();
+        trace.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(Trace::from_csv(&csv).unwrap().steps, trace.steps);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("step,command,start,end,zero_passes,total")
+        );
+        assert_eq!(lines.next(), Some("1,L68,50,82,1,1"));
+        assert_eq!(lines.count(), 9);
+
+        let other = run(&input, Dial::builder().size(99), Trace::new(50));
+        assert_eq!(trace.first_divergence(&trace), None);
+        assert_eq!(trace.first_divergence(&other), Some(0));
+        let shorter = run(&input[..6], Dial::builder(), Trace::new(50));
+        assert_eq!(trace.first_divergence(&shorter), Some(shorter.steps.len()));
+
+        let empty = run(&[], Dial::builder(), Trace::new(50));
+        assert_eq!(empty.state_after(0), Some((50, 0)));
+        assert_eq!(empty.state_after(1), None);
+    }
+
+    #[test]
+    fn test_grammar() {
+        let input =
+            b"# header\r\nL68, L30 R48\n\n  S5,R1,  # set then turn\nL18446744073709551615\n";
+        let commands = parse(input).unwrap();
+        assert_eq!(
+            commands,
+            vec![
+                Command::Left(68),
+                Command::Left(30),
+                Command::Right(48),
+                Command::Set(5),
+                Command::Right(1),
+                Command::Left(u64::MAX),
+            ]
+        );
+        let error = |line, column, kind| Err(ParseError { line, column, kind });
+        assert_eq!(
+            parse(b"L1\nR2 X3"),
+            error(2, 4, ParseErrorKind::UnknownCommand(b'X'))
+        );
+        assert_eq!(parse(b"L1,R"), error(1, 4, ParseErrorKind::MissingCount));
+        assert_eq!(
+            Batch::parse(b"L1,R", 100).err(),
+            error(1, 4, ParseErrorKind::MissingCount).err()
+        );
+        assert_eq!(parse(b"L1x"), error(1, 1, ParseErrorKind::InvalidCount));
+        assert_eq!(
+            parse(b"R18446744073709551616"),
+            error(1, 1, ParseErrorKind::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_zero_index() {
+        let mut rng = XorShift64::new(0x1d3e);
+        for _ in 0..200 {
+            let size = rng.range(1..=60);
+            let commands: Vec<_> = (0..rng.range(0..=40))
+                .map(|_| random_command(&mut rng, size))
+                .collect();
+            let index = ZeroIndex::new(&commands, size);
+            for _ in 0..20 {
+                let start = rng.range(0..=commands.len() as u64) as usize;
+                let end = rng.range(start as u64..=commands.len() as u64) as usize;
+                let position = rng.below(size);
+                let slice = &commands[start..end];
+                assert_eq!(
+                    (
+                        index.landings(start..end, position),
+                        index.passes(start..end, position)
+                    ),
+                    zero_counts(size, position, slice),
+                    "size {size}, start {position}, commands {slice:?}"
+                );
+                let mut dial = Dial::builder().size(size).start(position).build();
+                let last = slice.iter().fold(position, |_, cmd| dial.apply(cmd).to);
+                assert_eq!(index.position(start..end, position), last);
+            }
+        }
+
+        let size = u64::MAX;
+        let commands = [
+            Command::Left(1),
+            Command::Left(u64::MAX - 1),
+            Command::Set(3),
+            Command::Right(u64::MAX),
+        ];
+        let index = ZeroIndex::new(&commands, size);
+        assert_eq!(index.passes(0..4, 0), zero_counts(size, 0, &commands).1);
+        assert_eq!(index.passes(1..2, size - 1), 1);
+        assert_eq!(index.position(0..4, 0), 3);
+    }
+
+    #[test]
+    fn test_sweep() {
+        let mut rng = XorShift64::new(0x5ee9);
+        for _ in 0..200 {
+            let size = rng.range(1..=60);
+            let commands: Vec<_> = (0..rng.range(0..=40))
+                .map(|_| random_command(&mut rng, size))
+                .collect();
+            let sweep = Sweep::new(&commands, size);
+            let expected: Vec<_> = (0..size)
+                .map(|start| {
+                    let (part1, part2) = zero_counts(size, start, &commands);
+                    SweepRow {
+                        start,
+                        part1,
+                        part2,
+                    }
+                })
+                .collect();
+            assert_eq!(sweep.rows(), expected, "size {size}, commands {commands:?}");
+        }
+
+        let commands = parse(b"L68\nL30\nR48\nL5\nR60\nL55\nL1\nL99\nR14\nL82").unwrap();
+        let sweep = Sweep::new(&commands, 100);
+        assert_eq!(
+            sweep.rows()[50],
+            SweepRow {
+                start: 50,
+                part1: 3,
+                part2: 6
+            }
+        );
+        let mut csv = Vec::new();
+        sweep.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().nth(51), Some("50,3,6"));
+    }
+
+    // through the text, `Batch` only parses
+    fn batch(commands: &[Command], size: u64) -> Batch {
+        let text: Vec<_> = commands.iter().map(Command::to_string).collect();
+        Batch::parse(text.join(" ").as_bytes(), size).unwrap()
+    }
+
+    #[test]
+    fn test_batch() {
+        let mut rng = XorShift64::new(0xba7c);
+        for _ in 0..300 {
+            let size = rng.range(1..=150);
+            let start = rng.below(size);
+            let commands: Vec<_> = (0..rng.range(0..=60))
+                .map(|_| random_command(&mut rng, size))
+                .collect();
+            assert_eq!(
+                batch(&commands, size).solve(start),
+                zero_counts(size, start, &commands),
+                "size {size}, start {start}, commands {commands:?}"
+            );
+        }
+        let size = (1 << 62) - 1;
+        let commands = [
+            Command::Left(1),
+            Command::Right(u64::MAX),
+            Command::Set(size - 1),
+            Command::Right(1),
+        ];
+        assert_eq!(
+            batch(&commands, size).solve(0),
+            zero_counts(size, 0, &commands)
+        );
     }
 }
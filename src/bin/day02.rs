@@ -1,27 +1,710 @@
-use std::{fs::File, io::Read, ops::RangeInclusive, time::Instant};
+use advent_of_code::utils::parallel::Parallel;
+use advent_of_code::utils::ranges;
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    ops::RangeInclusive,
+    time::Instant,
+};
 
-#[derive(Clone)]
-struct Ranges(Vec<RangeInclusive<u64>>);
+#[derive(Clone, Debug)]
+struct Ranges(Vec<RangeInclusive<u128>>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ParseErrorKind {
+    MissingDash,
+    EmptyBound,
+    InvalidNumber,
+    Overflow,
+    Reversed,
+}
+
+/// Bad entry of a range list, `offset` is the byte where the entry starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ParseError {
+    offset: usize,
+    kind: ParseErrorKind,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "byte {}: ", self.offset)?;
+        match self.kind {
+            ParseErrorKind::MissingDash => write!(f, "expected `start-end`"),
+            ParseErrorKind::EmptyBound => write!(f, "missing bound"),
+            ParseErrorKind::InvalidNumber => write!(f, "bound isn't a number"),
+            ParseErrorKind::Overflow => write!(f, "bound doesn't fit in 128 bits"),
+            ParseErrorKind::Reversed => write!(f, "start is after the end"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn parse_bound(digits: &[u8]) -> Result<u128, ParseErrorKind> {
+    if digits.is_empty() {
+        return Err(ParseErrorKind::EmptyBound);
+    }
+    digits.iter().try_fold(0u128, |num, &b| {
+        if !b.is_ascii_digit() {
+            return Err(ParseErrorKind::InvalidNumber);
+        }
+        num.checked_mul(10)
+            .and_then(|n| n.checked_add((b - b'0') as u128))
+            .ok_or(ParseErrorKind::Overflow)
+    })
+}
 
 impl Ranges {
-    pub fn new(data: &str) -> Self {
+    /// `start-end` entries separated by commas, spaces or newlines (a trailing comma is
+    /// fine). The first bad entry stops the parsing.
+    pub fn parse(input: &[u8]) -> Result<Self, ParseError> {
         let mut ranges = Vec::new();
-        for range_data in data.split(|x| x == ',') {
-            if let Some((min, max)) = range_data.split_once(|x| x == '-') {
-                ranges.push(
-                    u64::from_str_radix(min, 10).unwrap()..=u64::from_str_radix(max, 10).unwrap(),
-                );
+        for entry in input
+            .split(|&b| b.is_ascii_whitespace() || b == b',')
+            .filter(|entry| !entry.is_empty())
+        {
+            let error = |kind| ParseError {
+                offset: entry.as_ptr() as usize - input.as_ptr() as usize,
+                kind,
+            };
+            let dash = entry
+                .iter()
+                .position(|&b| b == b'-')
+                .ok_or(error(ParseErrorKind::MissingDash))?;
+            let start = parse_bound(&entry[..dash]).map_err(error)?;
+            let end = parse_bound(&entry[dash + 1..]).map_err(error)?;
+            if start > end {
+                return Err(error(ParseErrorKind::Reversed));
             }
+            ranges.push(start..=end);
+        }
+        Ok(Self(ranges))
+    }
+
+    /// Warnings about a parsed list, which is still usable as is.
+    pub fn report(&self) -> ParseReport {
+        ParseReport {
+            overlaps: ranges::overlaps(&self.0),
+        }
+    }
+
+    /// Same IDs with overlapping and adjacent ranges merged, so none is counted twice.
+    pub fn merged(&self) -> Self {
+        Self(ranges::merge(&self.0))
+    }
+}
+
+/// What parsing noticed without rejecting the input.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct ParseReport {
+    // indices of the ranges sharing IDs, those get counted once per range
+    overlaps: Vec<(usize, usize)>,
+}
+
+impl ParseReport {
+    pub fn write(&self, ranges: &Ranges, mut out: impl Write) -> io::Result<()> {
+        for &(i, j) in &self.overlaps {
+            let (a, b) = (&ranges.0[i], &ranges.0[j]);
+            writeln!(
+                out,
+                "warning: ranges {} ({}-{}) and {} ({}-{}) overlap, their shared IDs are \
+                 counted twice without --merge",
+                i + 1,
+                a.start(),
+                a.end(),
+                j + 1,
+                b.start(),
+                b.end()
+            )?;
         }
-        Self(ranges)
+        Ok(())
+    }
+}
+
+/// A sum of IDs went past `u128::MAX`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SumOverflow;
+
+impl Display for SumOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "sum doesn't fit in 128 bits")
     }
 }
 
-fn solve_part1(ranges: Ranges) -> u64 {
+impl std::error::Error for SumOverflow {}
+
+#[inline]
+fn add(a: u128, b: u128) -> Result<u128, SumOverflow> {
+    a.checked_add(b).ok_or(SumOverflow)
+}
+
+// sum of `ids`, stopping at the first overflow
+fn checked_sum(mut ids: impl Iterator<Item = u128>) -> Result<u128, SumOverflow> {
+    ids.try_fold(0, add)
+}
+
+/// Property of an ID that day02 can look for in every range. Only [`IdPredicate::matches`]
+/// is needed, the other hooks let predicates with some structure skip the ID by ID scan.
+trait IdPredicate {
+    fn matches(&self, id: u128) -> bool;
+
+    /// Matching IDs of `range` in increasing order, `None` to fall back to testing them
+    /// all.
+    fn fast_enumerate(&self, _range: &RangeInclusive<u128>) -> Option<impl Iterator<Item = u128>> {
+        None::<std::iter::Empty<u128>>
+    }
+
+    /// Sum of the matching IDs of `range`, `None` to fall back to enumerating them.
+    fn fast_sum(&self, _range: &RangeInclusive<u128>) -> Option<Result<u128, SumOverflow>> {
+        None
+    }
+}
+
+/// Matching IDs of `range`, in increasing order.
+fn matching<P: IdPredicate>(
+    range: &RangeInclusive<u128>,
+    predicate: &P,
+) -> impl Iterator<Item = u128> {
+    let fast = predicate.fast_enumerate(range);
+    let brute = fast
+        .is_none()
+        .then(|| range.clone().filter(|&id| predicate.matches(id)));
+    fast.into_iter()
+        .flatten()
+        .chain(brute.into_iter().flatten())
+}
+
+/// Sum of the matching IDs of every range, using the fastest way the predicate offers.
+fn scan_sum<P: IdPredicate>(ranges: &Ranges, predicate: &P) -> Result<u128, SumOverflow> {
+    ranges.0.iter().try_fold(0, |acc, range| {
+        let sum = predicate
+            .fast_sum(range)
+            .unwrap_or_else(|| checked_sum(matching(range, predicate)))?;
+        add(acc, sum)
+    })
+}
+
+/// Same as [`scan_sum`] testing every ID, to cross-check the shortcuts.
+fn brute_sum<P: IdPredicate + Sync>(
+    ranges: &Ranges,
+    predicate: &P,
+    parallel: Parallel,
+) -> Result<u128, SumOverflow> {
+    parallel_scan(ranges, parallel, |range| {
+        checked_sum(range.filter(|&id| predicate.matches(id)))
+    })
+}
+
+/// Runs `scan` over every ID of the ranges on the threads of `parallel` and adds up the
+/// results. Wide ranges are cut in pieces so a single one can keep every thread busy,
+/// and the pieces are summed in order whatever the thread count.
+fn parallel_scan<F>(ranges: &Ranges, parallel: Parallel, scan: F) -> Result<u128, SumOverflow>
+where
+    F: Fn(RangeInclusive<u128>) -> Result<u128, SumOverflow> + Sync,
+{
+    // `0..=u128::MAX` alone has one ID too many to be counted
+    let total = ranges.0.iter().fold(0u128, |total, range| {
+        let len = (*range.end() - *range.start()).saturating_add(1);
+        total.saturating_add(len)
+    });
+    // a few pieces per thread, in case some of them are slower
+    let piece = (total / (parallel.threads() as u128 * 8)).max(1);
+    let mut pieces = Vec::new();
+    for range in &ranges.0 {
+        let (mut start, end) = (*range.start(), *range.end());
+        loop {
+            let last = start.saturating_add(piece - 1).min(end);
+            pieces.push(start..=last);
+            if last == end {
+                break;
+            }
+            start = last + 1;
+        }
+    }
+    // every piece is worth a thread, not just every 16Ki of them
+    parallel
+        .min_chunk(1)
+        .map_reduce(&pieces, |piece| scan(piece.clone()), |a, b| add(a?, b?))
+        .unwrap_or(Ok(0))
+}
+
+/// How many times the block has to be repeated. `AtMost` still asks for two copies or
+/// more, a single copy would match everything.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Repeats {
+    Exactly(u32),
+    AtLeast(u32),
+    AtMost(u32),
+}
+
+/// IDs whose digits in `base` are one block repeated a number of times allowed by
+/// `repeats`. A number matches as soon as one way of cutting it fits, `1111` is both
+/// two copies of `11` and four of `1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Repetition {
+    base: u32,
+    repeats: Repeats,
+}
+
+impl Repetition {
+    /// Part 1: exactly two copies in decimal.
+    pub const TWICE: Self = Self::new(10, Repeats::Exactly(2));
+    /// Part 2: two copies or more in decimal.
+    pub const REPEATED: Self = Self::new(10, Repeats::AtLeast(2));
+
+    pub const fn new(base: u32, repeats: Repeats) -> Self {
+        assert!(2 <= base && base <= 36, "base must be between 2 and 36");
+        let (Repeats::Exactly(k) | Repeats::AtLeast(k) | Repeats::AtMost(k)) = repeats;
+        assert!(k >= 2, "a block must be repeated at least twice");
+        Self { base, repeats }
+    }
+
+    #[inline]
+    fn allows(&self, count: u32) -> bool {
+        match self.repeats {
+            Repeats::Exactly(k) => count == k,
+            Repeats::AtLeast(k) => count >= k,
+            Repeats::AtMost(k) => (2..=k).contains(&count),
+        }
+    }
+
+    #[inline]
+    fn pow(&self, exp: u32) -> u128 {
+        (self.base as u128).pow(exp)
+    }
+
+    // number of digits of `n` in the base
+    #[inline]
+    fn digits(&self, n: u128) -> u32 {
+        n.checked_ilog(self.base as u128).unwrap_or(0) + 1
+    }
+
+    // `R = 1 0..01 0..01` with a 1 every `block` digits up to `len` digits, `None` when
+    // it's past `u128::MAX` and so is every repetition of that shape
+    fn repeat(&self, len: u32, block: u32) -> Option<u128> {
+        (0..len / block).try_fold(0u128, |acc, i| acc.checked_add(self.pow(block * i)))
+    }
+
+    // block values `B` such that `B * repeat` is in `range`
+    fn block_range(&self, range: &RangeInclusive<u128>, block: u32, repeat: u128) -> (u128, u128) {
+        let lo = range.start().div_ceil(repeat).max(self.pow(block - 1));
+        let hi = (range.end() / repeat).min(self.pow(block) - 1);
+        (lo, hi)
+    }
+
+    /// Sum of the `len`-digit numbers of `range` made of a `block`-digit block repeated
+    /// `len / block` times, `block` has to divide `len`.
+    fn block_sum(
+        &self,
+        range: &RangeInclusive<u128>,
+        len: u32,
+        block: u32,
+    ) -> Result<u128, SumOverflow> {
+        debug_assert!(len.is_multiple_of(block));
+        let Some(repeat) = self.repeat(len, block) else {
+            return Ok(0);
+        };
+        // the numbers are `block * repeat`, so `repeat` times an arithmetic series
+        let (lo, hi) = self.block_range(range, block, repeat);
+        if lo > hi {
+            return Ok(0);
+        }
+        // one of the two factors is even, halving it first keeps the product in range
+        let count = hi - lo + 1;
+        let series = match count % 2 {
+            0 => (count / 2).checked_mul(lo + hi),
+            _ => count.checked_mul((lo + hi) / 2),
+        };
+        series
+            .and_then(|series| series.checked_mul(repeat))
+            .ok_or(SumOverflow)
+    }
+
+    /// Sum of the `len`-digit numbers of `range` that match, each counted once.
+    fn len_sum(&self, range: &RangeInclusive<u128>, len: u32) -> Result<u128, SumOverflow> {
+        let divisors: Vec<_> = (1..len).filter(|&d| len.is_multiple_of(d)).collect();
+        let blocks: Vec<_> = divisors
+            .iter()
+            .copied()
+            .filter(|&d| self.allows(len / d))
+            .collect();
+        // sums by smallest period, skipping the periods no allowed block is made of (their
+        // sums can overflow when the answer doesn't)
+        let needed = |p: u32| blocks.iter().any(|block| block.is_multiple_of(p));
+        let mut primitive: Vec<u128> = Vec::with_capacity(divisors.len());
+        for (i, &d) in divisors.iter().enumerate() {
+            if !needed(d) {
+                primitive.push(0);
+                continue;
+            }
+            // the smaller periods are a subset of the numbers of `block_sum`
+            let smaller: u128 = divisors[..i]
+                .iter()
+                .zip(&primitive)
+                .filter(|&(&e, _)| d.is_multiple_of(e))
+                .map(|(_, sum)| sum)
+                .sum();
+            primitive.push(self.block_sum(range, len, d)? - smaller);
+        }
+        let sums = divisors
+            .iter()
+            .zip(&primitive)
+            .filter(|&(&p, _)| needed(p))
+            .map(|(_, &sum)| sum);
+        checked_sum(sums)
+    }
+
+    /// Shortest allowed block `n` is a repetition of and how many times it is repeated,
+    /// checked digit by digit.
+    pub fn cut(&self, n: u128) -> Option<(u128, u32)> {
+        let mut digits = Vec::with_capacity(128);
+        let mut rest = n;
+        loop {
+            digits.push(rest % self.base as u128);
+            rest /= self.base as u128;
+            if rest == 0 {
+                break;
+            }
+        }
+        let len = digits.len();
+        let count = (2..=len as u32)
+            .rev()
+            .filter(|&count| len.is_multiple_of(count as usize) && self.allows(count))
+            .find(|&count| {
+                let block = len / count as usize;
+                (block..len).all(|i| digits[i] == digits[i - block])
+            })?;
+        let block = n / self.pow(len as u32 - len as u32 / count);
+        Some((block, count))
+    }
+
+    /// Sum of the matching numbers of `range`, without looking at them one by one.
+    pub fn sum(&self, range: &RangeInclusive<u128>) -> Result<u128, SumOverflow> {
+        (self.digits(*range.start())..=self.digits(*range.end()))
+            .try_fold(0, |acc, len| add(acc, self.len_sum(range, len)?))
+    }
+}
+
+impl IdPredicate for Repetition {
+    fn matches(&self, id: u128) -> bool {
+        self.cut(id).is_some()
+    }
+
+    fn fast_enumerate(&self, range: &RangeInclusive<u128>) -> Option<impl Iterator<Item = u128>> {
+        let mut ids = Vec::new();
+        for len in self.digits(*range.start())..=self.digits(*range.end()) {
+            for block in (1..len).filter(|&d| len.is_multiple_of(d) && self.allows(len / d)) {
+                let Some(repeat) = self.repeat(len, block) else {
+                    continue;
+                };
+                let (lo, hi) = self.block_range(range, block, repeat);
+                ids.extend((lo..=hi).map(|b| b * repeat));
+            }
+        }
+        // a number can be cut in several allowed ways
+        ids.sort_unstable();
+        ids.dedup();
+        Some(ids.into_iter())
+    }
+
+    fn fast_sum(&self, range: &RangeInclusive<u128>) -> Option<Result<u128, SumOverflow>> {
+        Some(self.sum(range))
+    }
+}
+
+// number of decimal digits of `n`
+#[inline]
+fn decimal_len(n: u128) -> u32 {
+    n.checked_ilog10().unwrap_or(0) + 1
+}
+
+/// IDs that read the same both ways. Listed by mirroring their first half.
+#[derive(Clone, Copy, Debug)]
+struct Palindrome;
+
+impl IdPredicate for Palindrome {
+    fn matches(&self, id: u128) -> bool {
+        let mut buf = [0u8; 40];
+        let digits = to_decimal_buf(id, &mut buf).as_bytes();
+        digits.iter().eq(digits.iter().rev())
+    }
+
+    fn fast_enumerate(&self, range: &RangeInclusive<u128>) -> Option<impl Iterator<Item = u128>> {
+        let (lo, hi) = (*range.start(), *range.end());
+        let lengths = decimal_len(lo)..=decimal_len(hi);
+        Some(lengths.flat_map(move |len| {
+            let half = len.div_ceil(2);
+            let tail = 10u128.pow(len - half);
+            let first = if len == 1 { 0 } else { 10u128.pow(half - 1) };
+            // halves below the one of `lo` only give smaller palindromes
+            let first = match decimal_len(lo) == len {
+                true => first.max(lo / tail),
+                false => first,
+            };
+            (first..10u128.pow(half))
+                .map_while(move |h| {
+                    // the half without its middle digit, reversed after it
+                    let mut rest = if len % 2 == 1 { h / 10 } else { h };
+                    let mut mirrored = 0;
+                    while rest > 0 {
+                        mirrored = mirrored * 10 + rest % 10;
+                        rest /= 10;
+                    }
+                    // the longest ones can go past `u128::MAX`, and so do the next ones
+                    h.checked_mul(tail)?.checked_add(mirrored)
+                })
+                .skip_while(move |&id| id < lo)
+                .take_while(move |&id| id <= hi)
+        }))
+    }
+}
+
+/// IDs whose digits never go down from left to right. Listed by jumping from one to the
+/// next, there are only about 10 million of them with 20 digits or less.
+#[derive(Clone, Copy, Debug)]
+struct NonDecreasing;
+
+impl NonDecreasing {
+    // smallest matching ID `>= id`: at the first digit that goes down, it and everything
+    // after it are raised to the digit before
+    fn next_from(id: u128) -> Option<u128> {
+        let mut buf = [0u8; 40];
+        let mut digits = to_decimal_buf(id, &mut buf).as_bytes().to_vec();
+        if let Some(i) = (1..digits.len()).find(|&i| digits[i] < digits[i - 1]) {
+            let fill = digits[i - 1];
+            digits[i..].fill(fill);
+        }
+        str::from_utf8(&digits).unwrap().parse().ok()
+    }
+}
+
+impl IdPredicate for NonDecreasing {
+    fn matches(&self, id: u128) -> bool {
+        let mut buf = [0u8; 40];
+        let digits = to_decimal_buf(id, &mut buf).as_bytes();
+        digits.is_sorted()
+    }
+
+    fn fast_enumerate(&self, range: &RangeInclusive<u128>) -> Option<impl Iterator<Item = u128>> {
+        let hi = *range.end();
+        let ids = std::iter::successors(Self::next_from(*range.start()), |&id| {
+            Self::next_from(id.checked_add(1)?)
+        });
+        Some(ids.take_while(move |&id| id <= hi))
+    }
+}
+
+/// IDs whose digits add up to the given value. Summed with a digit DP over the bounds
+/// of each range.
+#[derive(Clone, Copy, Debug)]
+struct DigitSum(u32);
+
+impl DigitSum {
+    // sum of the IDs of `0..=bound` whose digits add up to the target
+    fn sum_up_to(&self, bound: u128) -> Result<u128, SumOverflow> {
+        let mut buf = [0u8; 40];
+        let digits: Vec<_> = to_decimal_buf(bound, &mut buf)
+            .bytes()
+            .map(|b| (b - b'0') as u32)
+            .collect();
+        let target = self.0 as usize;
+        if target > 9 * digits.len() {
+            return Ok(0);
+        }
+        // count[len][t] and total[len][t]: number and sum of the `len`-digit strings
+        // (leading zeros allowed) whose digits add up to `t`. A total is `None` once it
+        // overflows, which only matters if it's part of the answer.
+        let mut count = vec![vec![0u128; target + 1]; digits.len()];
+        let mut total = vec![vec![Some(0u128); target + 1]; digits.len()];
+        count[0][0] = 1;
+        for len in 1..digits.len() {
+            for t in 0..=target {
+                for d in 0..=t.min(9) {
+                    count[len][t] += count[len - 1][t - d];
+                    let leading =
+                        (d as u128 * 10u128.pow(len as u32 - 1)).checked_mul(count[len - 1][t - d]);
+                    total[len][t] = total[len][t]
+                        .zip(leading)
+                        .zip(total[len - 1][t - d])
+                        .and_then(|((acc, leading), rest)| {
+                            acc.checked_add(leading)?.checked_add(rest)
+                        });
+                }
+            }
+        }
+        // every ID below `bound` shares a prefix with it then has a smaller digit
+        let (mut acc, mut prefix, mut used) = (0, 0u128, 0);
+        for (i, &digit) in digits.iter().enumerate() {
+            let rest = digits.len() - 1 - i;
+            for d in 0..digit {
+                let Some(t) = target.checked_sub(used + d as usize) else {
+                    break;
+                };
+                if t <= 9 * rest {
+                    let sum = ((prefix * 10 + d as u128) * 10u128.pow(rest as u32))
+                        .checked_mul(count[rest][t])
+                        .zip(total[rest][t])
+                        .and_then(|(leading, rest)| leading.checked_add(rest));
+                    acc = add(acc, sum.ok_or(SumOverflow)?)?;
+                }
+            }
+            prefix = prefix * 10 + digit as u128;
+            used += digit as usize;
+        }
+        if used == target {
+            acc = add(acc, bound)?;
+        }
+        Ok(acc)
+    }
+}
+
+impl IdPredicate for DigitSum {
+    fn matches(&self, id: u128) -> bool {
+        let mut buf = [0u8; 40];
+        let digits = to_decimal_buf(id, &mut buf).bytes();
+        digits.map(|b| (b - b'0') as u32).sum::<u32>() == self.0
+    }
+
+    fn fast_sum(&self, range: &RangeInclusive<u128>) -> Option<Result<u128, SumOverflow>> {
+        let below = match range.start().checked_sub(1) {
+            Some(end) => self.sum_up_to(end),
+            None => Ok(0),
+        };
+        // `below` is part of the total, it can't overflow on its own
+        Some(
+            self.sum_up_to(*range.end())
+                .map(|total| total - below.unwrap()),
+        )
+    }
+}
+
+/// One matching ID as listed by an [`Audit`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct AuditEntry {
+    // index of the range in the input
+    range: usize,
+    id: u128,
+    block: u128,
+    repeats: u32,
+}
+
+/// Every ID matched by a [`Repetition`], with the range it comes from and how it is cut,
+/// to compare individual IDs with another implementation.
+#[derive(Debug)]
+struct Audit<'a> {
+    ranges: &'a Ranges,
+    repetition: Repetition,
+    entries: Vec<AuditEntry>,
+}
+
+impl<'a> Audit<'a> {
+    pub fn new(ranges: &'a Ranges, repetition: Repetition) -> Self {
+        let entries = ranges
+            .0
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, range)| {
+                matching(range, &repetition).map(move |id| {
+                    let (block, repeats) = repetition.cut(id).unwrap();
+                    AuditEntry {
+                        range: idx,
+                        id,
+                        block,
+                        repeats,
+                    }
+                })
+            })
+            .collect();
+        Self {
+            ranges,
+            repetition,
+            entries,
+        }
+    }
+
+    /// Number of matching IDs in each range, in input order.
+    pub fn counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.ranges.0.len()];
+        for entry in &self.entries {
+            counts[entry.range] += 1;
+        }
+        counts
+    }
+
+    // the block written in the repetition's base
+    fn block(&self, entry: &AuditEntry) -> String {
+        let base = self.repetition.base;
+        let mut digits = Vec::new();
+        let mut rest = entry.block;
+        loop {
+            digits.push(char::from_digit((rest % base as u128) as u32, base).unwrap());
+            rest /= base as u128;
+            if rest == 0 {
+                break;
+            }
+        }
+        digits.iter().rev().collect()
+    }
+
+    /// One row per ID, `count` repeats how many IDs its range has.
+    pub fn write_csv(&self, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "range,count,id,block,repeats")?;
+        let counts = self.counts();
+        for entry in &self.entries {
+            let range = &self.ranges.0[entry.range];
+            writeln!(
+                out,
+                "{}-{},{},{},{},{}",
+                range.start(),
+                range.end(),
+                counts[entry.range],
+                entry.id,
+                self.block(entry),
+                entry.repeats
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Audit<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut entries = self.entries.iter().peekable();
+        for (idx, (range, count)) in self.ranges.0.iter().zip(self.counts()).enumerate() {
+            writeln!(f, "{}-{}: {count} IDs", range.start(), range.end())?;
+            while let Some(entry) = entries.next_if(|entry| entry.range == idx) {
+                let block = self.block(entry);
+                writeln!(f, "    {} = {block} x{}", entry.id, entry.repeats)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn solve<P: IdPredicate>(ranges: &Ranges, predicate: &P) -> Result<u128, SumOverflow> {
     let now = Instant::now();
-    let mut buf = [0u8; 20];
-    let mut acc = 0;
-    for range in ranges.0 {
+    let acc = scan_sum(ranges, predicate);
+    let elapsed = now.elapsed();
+    println!("took: {:?}", elapsed);
+    acc
+}
+
+fn solve_part1(ranges: &Ranges) -> Result<u128, SumOverflow> {
+    solve(ranges, &Repetition::TWICE)
+}
+
+fn solve_part2(ranges: &Ranges) -> Result<u128, SumOverflow> {
+    solve(ranges, &Repetition::REPEATED)
+}
+
+// number by number versions, kept to cross-check the closed forms
+fn brute_part1(ranges: &Ranges, parallel: Parallel) -> Result<u128, SumOverflow> {
+    let now = Instant::now();
+    let acc = parallel_scan(ranges, parallel, |range| {
+        let mut buf = [0u8; 40];
+        let mut acc = 0;
         for num in range {
             let num_str = to_decimal_buf(num, &mut buf);
 
@@ -34,37 +717,56 @@ fn solve_part1(ranges: Ranges) -> u64 {
 
             let mid = len / 2;
             if num_str[..mid] == num_str[mid..] {
-                acc += num;
+                acc = add(acc, num)?;
             }
         }
-    }
+        Ok(acc)
+    });
     let elapsed = now.elapsed();
     println!("took: {:?}", elapsed);
     acc
 }
 
-fn solve_part2(ranges: Ranges) -> u64 {
+fn brute_part2(ranges: &Ranges, parallel: Parallel) -> Result<u128, SumOverflow> {
     let now = Instant::now();
-    let mut acc = 0;
-    let mut buf = [0u8; 20];
-    for range in ranges.0 {
+    let acc = parallel_scan(ranges, parallel, |range| {
+        let mut acc = 0;
+        let mut buf = [0u8; 40];
         for num in range {
             let num_str = to_decimal_buf(num, &mut buf);
             let mid = num_str.len() / 2;
             for i in (1..=mid).rev() {
                 let count = num_str.matches(&num_str[..i]).count();
                 if count > 1 && ((i * count) == num_str.len()) {
-                    acc += num;
+                    acc = add(acc, num)?;
                     break;
                 }
             }
         }
-    }
+        Ok(acc)
+    });
     let elapsed = now.elapsed();
     println!("took: {:?}", elapsed);
     acc
 }
 
+// the sum, or why there is none
+fn show(sum: Result<u128, SumOverflow>) -> String {
+    sum.map_or_else(|err| err.to_string(), |sum| sum.to_string())
+}
+
+// answer for another predicate, and the ID by ID scan when asked
+fn report<P>(ranges: &Ranges, predicate: P, brute: Option<Parallel>)
+where
+    P: IdPredicate + Sync + std::fmt::Debug,
+{
+    println!("{predicate:?}: {}", show(solve(ranges, &predicate)));
+    if let Some(parallel) = brute {
+        let sum = brute_sum(ranges, &predicate, parallel);
+        println!("{predicate:?} (brute): {}", show(sum));
+    }
+}
+
 fn main() {
     let mut buf = Vec::new();
     File::open("inputs/day02.txt")
@@ -72,14 +774,99 @@ fn main() {
         .read_to_end(&mut buf)
         .unwrap();
 
-    let ranges = Ranges::new(str::from_utf8(&buf).unwrap().trim());
+    // `--brute` to also run the number by number scan, `--base B` with one of
+    // `--exactly K`, `--at-least K` or `--at-most K` for other kinds of repetitions,
+    // `--palindromes`, `--non-decreasing` and `--digit-sum S` for other digit patterns,
+    // `--threads N` to choose how many threads the brute force scans use,
+    // `--audit FILE` to list the IDs of part 2 as CSV (`-` prints them by range), or
+    // those of the repetition chosen above (`--exactly 2` for part 1),
+    // `--merge` to merge overlapping ranges first so shared IDs are only counted once
+    let (mut brute, mut base, mut repeats) = (false, 10, None);
+    let mut parallel = Parallel::new();
+    let (mut audit, mut merge) = (None, false);
+    let (mut palindromes, mut non_decreasing, mut digit_sum) = (false, false, None);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || -> u32 {
+            let value = args.next().expect("missing value after option");
+            value.parse().unwrap()
+        };
+        match arg.as_str() {
+            "--brute" => brute = true,
+            "--base" => base = value(),
+            "--exactly" => repeats = Some(Repeats::Exactly(value())),
+            "--at-least" => repeats = Some(Repeats::AtLeast(value())),
+            "--at-most" => repeats = Some(Repeats::AtMost(value())),
+            "--palindromes" => palindromes = true,
+            "--non-decreasing" => non_decreasing = true,
+            "--digit-sum" => digit_sum = Some(DigitSum(value())),
+            "--audit" => audit = args.next(),
+            "--merge" => merge = true,
+            "--threads" => parallel = Parallel::with_threads(value() as usize),
+            _ => panic!(
+                "usage: day02 [--brute] [--base B] [--exactly K | --at-least K | --at-most K] \
+                 [--palindromes] [--non-decreasing] [--digit-sum S] [--threads N] \
+                 [--audit FILE] [--merge]"
+            ),
+        }
+    }
+
+    let ranges = match Ranges::parse(&buf) {
+        Ok(ranges) => ranges,
+        Err(err) => {
+            eprintln!("inputs/day02.txt: {err}");
+            std::process::exit(1);
+        }
+    };
+    let parse_report = ranges.report();
+    let ranges = if merge {
+        ranges.merged()
+    } else {
+        parse_report.write(&ranges, io::stderr()).unwrap();
+        ranges
+    };
 
-    println!("Part 1: {}", solve_part1(ranges.clone()));
-    println!("Part 2: {}", solve_part2(ranges));
+    println!("Part 1: {}", show(solve_part1(&ranges)));
+    println!("Part 2: {}", show(solve_part2(&ranges)));
+    if brute {
+        let serial = Parallel::with_threads(1);
+        let part1 = brute_part1(&ranges, serial);
+        println!("Part 1 (brute, serial): {}", show(part1));
+        let part2 = brute_part2(&ranges, serial);
+        println!("Part 2 (brute, serial): {}", show(part2));
+        let threads = parallel.threads();
+        let part1 = brute_part1(&ranges, parallel);
+        println!("Part 1 (brute, {threads} threads): {}", show(part1));
+        let part2 = brute_part2(&ranges, parallel);
+        println!("Part 2 (brute, {threads} threads): {}", show(part2));
+    }
+    let brute = brute.then_some(parallel);
+    let repetition = Repetition::new(base, repeats.unwrap_or(Repeats::AtLeast(2)));
+    if repeats.is_some() || base != 10 {
+        report(&ranges, repetition, brute);
+    }
+    if let Some(path) = audit {
+        let audit = Audit::new(&ranges, repetition);
+        match path.as_str() {
+            "-" => print!("{audit}"),
+            path => audit
+                .write_csv(BufWriter::new(File::create(path).unwrap()))
+                .unwrap(),
+        }
+    }
+    if palindromes {
+        report(&ranges, Palindrome, brute);
+    }
+    if non_decreasing {
+        report(&ranges, NonDecreasing, brute);
+    }
+    if let Some(digit_sum) = digit_sum {
+        report(&ranges, digit_sum, brute);
+    }
 }
 
-fn to_decimal_buf(mut n: u64, out: &mut [u8; 20]) -> &str {
-    let mut i = 20;
+fn to_decimal_buf(mut n: u128, out: &mut [u8; 40]) -> &str {
+    let mut i = 40;
     loop {
         i -= 1;
         out[i] = b'0' + (n % 10) as u8;
@@ -93,15 +880,22 @@ fn to_decimal_buf(mut n: u64, out: &mut [u8; 20]) -> &str {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Ranges, solve_part1, solve_part2};
+    use crate::{
+        Audit, DigitSum, IdPredicate, NonDecreasing, Palindrome, ParseError, ParseErrorKind,
+        ParseReport, Ranges, Repeats, Repetition, SumOverflow, brute_part1, brute_part2, brute_sum,
+        matching, scan_sum, solve_part1, solve_part2,
+    };
+    use advent_of_code::utils::parallel::Parallel;
+    use advent_of_code::utils::rng::XorShift64;
 
     #[test]
     fn test_pt1() {
         let value = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124";
 
-        let ranges = Ranges::new(value);
+        let ranges = Ranges::parse(value.as_bytes()).unwrap();
 
-        let result = solve_part1(ranges);
+        assert_eq!(brute_part1(&ranges, Parallel::new()), Ok(1227775554));
+        let result = solve_part1(&ranges).unwrap();
         assert_eq!(result, 1227775554);
     }
 
@@ -109,9 +903,247 @@ mod tests {
     fn test_pt2() {
         let value = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124";
 
-        let ranges = Ranges::new(value);
+        let ranges = Ranges::parse(value.as_bytes()).unwrap();
 
-        let result = solve_part2(ranges);
+        assert_eq!(brute_part2(&ranges, Parallel::new()), Ok(4174379265));
+        let result = solve_part2(&ranges).unwrap();
         assert_eq!(result, 4174379265);
     }
+
+    #[test]
+    fn test_closed_form() {
+        let mut rng = XorShift64::new(0xd2);
+        for _ in 0..100 {
+            let digits = rng.range(1..=10) as u32;
+            let start = rng.below(10u64.pow(digits)) as u128;
+            let end = start + rng.below(5_000) as u128;
+            let ranges = Ranges(vec![start..=end]);
+            assert_eq!(
+                solve_part1(&ranges),
+                brute_part1(&ranges, Parallel::new()),
+                "{start}-{end}"
+            );
+            assert_eq!(
+                solve_part2(&ranges),
+                brute_part2(&ranges, Parallel::new()),
+                "{start}-{end}"
+            );
+        }
+        assert_eq!(solve_part1(&Ranges(vec![1..=99])), Ok(495));
+        assert_eq!(solve_part2(&Ranges(vec![1..=999])), Ok(495 + 4995));
+        // the widest ranges go through in no time: every 17-digit number with a repeated
+        // block is a repdigit since 17 is prime
+        let ranges = Ranges(vec![10u128.pow(16)..=10u128.pow(17) - 1]);
+        assert_eq!(solve_part1(&ranges), Ok(0));
+        assert_eq!(solve_part2(&ranges), Ok(5 * (10u128.pow(17) - 1)));
+        let ranges = Ranges(vec![1..=9_999_999_999]);
+        assert!(solve_part2(&ranges).unwrap() > solve_part1(&ranges).unwrap());
+    }
+
+    #[test]
+    fn test_parse() {
+        let ranges = Ranges::parse(b"11-22, 95-115\n998-1012,\r\n  5-5,\n").unwrap();
+        assert_eq!(ranges.0, vec![11..=22, 95..=115, 998..=1012, 5..=5]);
+        assert!(Ranges::parse(b"").unwrap().0.is_empty());
+
+        let error = |offset, kind| Err(ParseError { offset, kind });
+        let parse = |input: &[u8]| Ranges::parse(input).map(|ranges| ranges.0);
+        assert_eq!(parse(b"1-2,34"), error(4, ParseErrorKind::MissingDash));
+        assert_eq!(parse(b"1-2, -5"), error(5, ParseErrorKind::EmptyBound));
+        assert_eq!(parse(b"1-,3-4"), error(0, ParseErrorKind::EmptyBound));
+        assert_eq!(parse(b"1-2\n3-x"), error(4, ParseErrorKind::InvalidNumber));
+        assert_eq!(
+            parse(b"0-340282366920938463463374607431768211456"),
+            error(0, ParseErrorKind::Overflow)
+        );
+        assert_eq!(parse(b"1-2,9-3"), error(4, ParseErrorKind::Reversed));
+    }
+
+    #[test]
+    fn test_repetition() {
+        let decimal = |repeats| Repetition::new(10, repeats);
+        assert!(decimal(Repeats::Exactly(2)).matches(1111));
+        assert!(decimal(Repeats::Exactly(4)).matches(1111));
+        assert!(!decimal(Repeats::Exactly(3)).matches(1111));
+        assert!(decimal(Repeats::AtMost(3)).matches(121212));
+        assert!(!decimal(Repeats::AtMost(2)).matches(121212));
+        assert!(Repetition::new(2, Repeats::Exactly(2)).matches(0b1010));
+        assert!(Repetition::new(36, Repeats::AtLeast(2)).matches(37 * 36 * 36 + 37));
+
+        let mut rng = XorShift64::new(0x40);
+        for _ in 0..300 {
+            let base = rng.range(2..=36) as u32;
+            let k = rng.range(2..=4) as u32;
+            let repeats = [Repeats::Exactly(k), Repeats::AtLeast(k), Repeats::AtMost(k)];
+            let repetition = Repetition::new(base, repeats[rng.below(3) as usize]);
+            let bits = rng.range(1..=40);
+            let start = rng.below(1 << bits) as u128;
+            let range = start..=start + rng.below(2000) as u128;
+            let expected: u128 = range.clone().filter(|&n| repetition.matches(n)).sum();
+            assert_eq!(
+                repetition.sum(&range),
+                Ok(expected),
+                "{repetition:?} {range:?}"
+            );
+        }
+    }
+
+    // the shortcuts of `predicate` against testing every ID of random ranges
+    fn check_shortcuts<P: IdPredicate + Sync + std::fmt::Debug>(predicate: P, seed: u64) {
+        let mut rng = XorShift64::new(seed);
+        for _ in 0..100 {
+            let bits = rng.range(1..=40);
+            let start = rng.below(1 << bits) as u128;
+            let range = start..=start + rng.below(3000) as u128;
+            let expected: Vec<_> = range.clone().filter(|&id| predicate.matches(id)).collect();
+            assert_eq!(
+                matching(&range, &predicate).collect::<Vec<_>>(),
+                expected,
+                "{predicate:?} {range:?}"
+            );
+            let ranges = Ranges(vec![range]);
+            assert_eq!(
+                scan_sum(&ranges, &predicate),
+                brute_sum(&ranges, &predicate, Parallel::new())
+            );
+        }
+    }
+
+    #[test]
+    fn test_predicates() {
+        check_shortcuts(Repetition::REPEATED, 1);
+        check_shortcuts(Repetition::new(3, Repeats::AtMost(3)), 2);
+        check_shortcuts(Palindrome, 3);
+        check_shortcuts(NonDecreasing, 4);
+        check_shortcuts(DigitSum(7), 5);
+        check_shortcuts(DigitSum(30), 6);
+
+        let ranges = Ranges(vec![0..=u128::MAX]);
+        assert!(Palindrome.matches(12321) && !Palindrome.matches(1232));
+        assert!(NonDecreasing.matches(1123) && !NonDecreasing.matches(1132));
+        assert_eq!(
+            matching(&(u128::MAX - 100..=u128::MAX), &NonDecreasing).count(),
+            0
+        );
+        assert_eq!(scan_sum(&ranges, &DigitSum(0)), Ok(0));
+        assert_eq!(
+            scan_sum(&ranges, &DigitSum(1)),
+            Ok((0..39).map(|e| 10u128.pow(e)).sum())
+        );
+        assert_eq!(scan_sum(&ranges, &DigitSum(9)), Err(SumOverflow));
+        assert_eq!(
+            matching(&(u128::MAX - 10u128.pow(20)..=u128::MAX), &Palindrome).last(),
+            Some(340282366920938463454364839029663282043)
+        );
+    }
+
+    #[test]
+    fn test_threads() {
+        let ranges = Ranges::parse(b"11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124,1-100000").unwrap();
+        let expected = (
+            brute_part1(&ranges, Parallel::with_threads(1)),
+            brute_part2(&ranges, Parallel::with_threads(1)),
+        );
+        assert_eq!(expected, (solve_part1(&ranges), solve_part2(&ranges)));
+        for threads in [2, 3, 8, 64] {
+            let parallel = Parallel::with_threads(threads);
+            let answers = (
+                brute_part1(&ranges, parallel),
+                brute_part2(&ranges, parallel),
+            );
+            assert_eq!(answers, expected, "{threads} threads");
+            let palindromes = brute_sum(&ranges, &Palindrome, parallel);
+            assert_eq!(palindromes, scan_sum(&ranges, &Palindrome));
+        }
+        let edge = Ranges(vec![u128::MAX - 5..=u128::MAX, 0..=0]);
+        assert_eq!(
+            brute_sum(&edge, &Palindrome, Parallel::with_threads(4)),
+            scan_sum(&edge, &Palindrome)
+        );
+    }
+
+    #[test]
+    fn test_audit() {
+        let ranges = Ranges::parse(b"11-22,95-115,998-1012,222220-222224").unwrap();
+        let audit = Audit::new(&ranges, Repetition::REPEATED);
+        assert_eq!(audit.counts(), vec![2, 2, 2, 1]);
+        let sum: u128 = audit.entries.iter().map(|entry| entry.id).sum();
+        assert_eq!(Ok(sum), solve_part2(&ranges));
+        assert_eq!((audit.entries[3].block, audit.entries[3].repeats), (1, 3));
+        assert_eq!((audit.entries[5].block, audit.entries[5].repeats), (10, 2));
+
+        let mut csv = Vec::new();
+        audit.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines[0], "range,count,id,block,repeats");
+        assert_eq!(lines[3], "95-115,2,99,9,2");
+        assert_eq!(lines[7], "222220-222224,1,222222,2,6");
+
+        let twice = Audit::new(&ranges, Repetition::TWICE);
+        assert_eq!(twice.counts(), vec![2, 1, 1, 1]);
+        assert_eq!(twice.entries[4].block, 222);
+        let hex_ranges = Ranges(vec![0xab00..=0xabff]);
+        let hex = Audit::new(&hex_ranges, Repetition::new(16, Repeats::AtLeast(2)));
+        assert_eq!(hex.to_string(), "43776-44031: 1 IDs\n    43947 = ab x2\n");
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let ranges = Ranges::parse(b"11-22,95-115,20-30,31-40,99-99").unwrap();
+        let report = ranges.report();
+        assert_eq!(
+            report,
+            ParseReport {
+                overlaps: vec![(0, 2), (1, 4)]
+            }
+        );
+        let mut warnings = Vec::new();
+        report.write(&ranges, &mut warnings).unwrap();
+        let warnings = String::from_utf8(warnings).unwrap();
+        assert_eq!(warnings.lines().count(), 2);
+        assert!(warnings.starts_with("warning: ranges 1 (11-22) and 3 (20-30) overlap"));
+
+        let merged = ranges.merged();
+        assert_eq!(merged.0, vec![11..=40, 95..=115]);
+        assert!(merged.report().overlaps.is_empty());
+        // 22 and 99 are counted twice before merging
+        assert_eq!(
+            solve_part2(&ranges),
+            Ok(solve_part2(&merged).unwrap() + 22 + 99)
+        );
+        assert_eq!(brute_part1(&merged, Parallel::new()), solve_part1(&merged));
+    }
+
+    #[test]
+    fn test_wide_ids() {
+        // 38 digits, past `u64::MAX`
+        let id = 12345678901234567891234567890123456789u128;
+        let ranges = Ranges::parse(format!("{}-{}", id - 1000, id + 1000).as_bytes()).unwrap();
+        assert_eq!(solve_part1(&ranges), Ok(id));
+        assert_eq!(brute_part1(&ranges, Parallel::new()), Ok(id));
+        assert_eq!(solve_part2(&ranges), brute_part2(&ranges, Parallel::new()));
+        let audit = Audit::new(&ranges, Repetition::TWICE);
+        assert_eq!(audit.entries[0].block, 1234567890123456789);
+        // 128 ones in binary
+        let binary = Repetition::new(2, Repeats::Exactly(2));
+        assert_eq!(binary.cut(u128::MAX), Some((u64::MAX as u128, 2)));
+        let top = Ranges(vec![u128::MAX - 3..=u128::MAX]);
+        assert_eq!(scan_sum(&top, &binary), Ok(u128::MAX));
+        assert_eq!(brute_sum(&top, &binary, Parallel::new()), Ok(u128::MAX));
+
+        // the overflow is reported by the closed forms and the scans alike
+        let ranges = Ranges(vec![3..=3, u128::MAX - 3..=u128::MAX]);
+        assert_eq!(scan_sum(&ranges, &binary), Err(SumOverflow));
+        assert_eq!(
+            brute_sum(&ranges, &binary, Parallel::new()),
+            Err(SumOverflow)
+        );
+        let ranges = Ranges(vec![10u128.pow(37)..=u128::MAX]);
+        assert_eq!(solve_part1(&ranges), Err(SumOverflow));
+        assert_eq!(solve_part2(&ranges), Err(SumOverflow));
+        // a single repetition of a 19-digit block fits even though every 38-digit one doesn't
+        let ranges = Ranges(vec![10u128.pow(37)..=10u128.pow(37) + 10u128.pow(19)]);
+        assert_eq!(solve_part1(&ranges), Ok(10u128.pow(37) + 10u128.pow(18)));
+    }
 }
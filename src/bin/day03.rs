@@ -1,61 +1,494 @@
-use std::{fs::File, io::Read, time::Instant};
-
-const POW10: [u64; 13] = [
-    1,
-    10,
-    100,
-    1_000,
-    10_000,
-    100_000,
-    1_000_000,
-    10_000_000,
-    100_000_000,
-    1_000_000_000,
-    10_000_000_000,
-    100_000_000_000,
-    1_000_000_000_000,
-];
-
-fn solve_part1(buf: &[u8]) -> u64 {
+use advent_of_code::utils::LineIterator;
+use advent_of_code::utils::bignum::BigUint;
+use advent_of_code::utils::parallel::Parallel;
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    time::Instant,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ParseErrorKind {
+    NotADigit(u8),
+    TooShort { len: usize, needed: usize },
+}
+
+/// Bad bank, `line` and `column` start from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ParseError {
+    line: usize,
+    column: usize,
+    kind: ParseErrorKind,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match self.kind {
+            ParseErrorKind::NotADigit(b) => write!(f, "'{}' isn't a battery", b.escape_ascii()),
+            ParseErrorKind::TooShort { len, needed } => {
+                write!(f, "bank has {len} batteries, {needed} are needed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// What [`Banks::parse`] does with a bad bank.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Policy {
+    /// Stop at the first one.
+    Error,
+    /// Leave it out and keep the error as a warning.
+    Skip,
+}
+
+/// One line of the input, `number` starts from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Bank<'a> {
+    number: usize,
+    line: &'a [u8],
+}
+
+/// The valid banks of an input, and the errors of those left out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Banks<'a> {
+    banks: Vec<Bank<'a>>,
+    skipped: Vec<ParseError>,
+}
+
+impl<'a> Banks<'a> {
+    /// One bank per non-empty line, made of digits only and at least `min_len` of them.
+    /// Give the largest battery count that will be asked for, so with [`Policy::Skip`]
+    /// every answer is over the same banks.
+    pub fn parse(input: &'a [u8], min_len: usize, policy: Policy) -> Result<Self, ParseError> {
+        let mut banks = Self::default();
+        for (idx, line) in LineIterator::new(input).enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let error = |column, kind| ParseError {
+                line: idx + 1,
+                column,
+                kind,
+            };
+            let invalid = match line.iter().position(|b| !b.is_ascii_digit()) {
+                Some(pos) => Some(error(pos + 1, ParseErrorKind::NotADigit(line[pos]))),
+                None if line.len() < min_len => {
+                    let kind = ParseErrorKind::TooShort {
+                        len: line.len(),
+                        needed: min_len,
+                    };
+                    Some(error(line.len() + 1, kind))
+                }
+                None => None,
+            };
+            match (invalid, policy) {
+                (None, _) => banks.banks.push(Bank {
+                    number: idx + 1,
+                    line,
+                }),
+                (Some(err), Policy::Error) => return Err(err),
+                (Some(err), Policy::Skip) => banks.skipped.push(err),
+            }
+        }
+        Ok(banks)
+    }
+}
+
+/// Batteries turned on in a bank: their positions in the line, increasing, and the
+/// joltage they make read in that order. Banks can be long enough for the joltage to
+/// outgrow any integer type, [`BigUint::to_u128`] gives it back when it fits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Selection {
+    positions: Vec<usize>,
+    joltage: BigUint,
+}
+
+impl Selection {
+    fn new(line: &[u8], positions: Vec<usize>) -> Self {
+        let mut joltage = BigUint::zero();
+        for &i in &positions {
+            joltage.push_digit(line[i] - b'0');
+        }
+        Self { positions, joltage }
+    }
+}
+
+/// How the batteries of a bank are chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Objective {
+    /// Largest joltage, the puzzle's.
+    Max,
+    /// Smallest joltage.
+    Min,
+    /// Largest joltage without turning on two neighbouring batteries.
+    MaxNonAdjacent,
+    /// Largest joltage that is a multiple of the value, at most [`Config::MAX_DIVISOR`].
+    MaxDivisible(u64),
+    /// Largest sum of the digits, then the largest joltage among those.
+    MaxDigitSum,
+}
+
+/// What day03 solves: how many batteries to turn on in each bank and how to choose them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Config {
+    digits: usize,
+    objective: Objective,
+}
+
+impl Config {
+    /// Part 1: the largest pair.
+    pub const PART1: Self = Self::new(2, Objective::Max);
+    /// Part 2: the largest 12 batteries.
+    pub const PART2: Self = Self::new(12, Objective::Max);
+    /// The search keeps a table entry per remainder for every position and digit count.
+    pub const MAX_DIVISOR: u64 = 10_000;
+
+    pub const fn new(digits: usize, objective: Objective) -> Self {
+        if let Objective::MaxDivisible(m) = objective {
+            assert!(m > 0, "can't divide by zero");
+            assert!(m <= Self::MAX_DIVISOR, "divisor above 10000");
+        }
+        Self { digits, objective }
+    }
+
+    /// Best batteries of `line`, `None` when no choice of `digits` of them satisfies the
+    /// objective (a bank too short, no multiple of the value, ...).
+    pub fn select(&self, line: &[u8]) -> Option<Selection> {
+        let k = self.digits;
+        if line.len() < k {
+            return None;
+        }
+        let positions = match self.objective {
+            Objective::Max => return Some(select(line, k)),
+            Objective::Min => monotonic(line, k, |top, n| top > n),
+            Objective::MaxNonAdjacent => pick_largest(line, k, 2, (), |_, _, _| Some(()))?,
+            Objective::MaxDivisible(m) => max_divisible(line, k, m)?,
+            Objective::MaxDigitSum => max_digit_sum(line, k)?,
+        };
+        Some(Selection::new(line, positions))
+    }
+
+    /// Sum of the joltages of every bank, those without a selection add nothing.
+    pub fn total(&self, banks: &Banks) -> BigUint {
+        // every bank is independent, each thread gets at least `MIN_CHUNK` batteries
+        let batteries: usize = banks.banks.iter().map(|bank| bank.line.len()).sum();
+        let per_bank = batteries.div_ceil(banks.banks.len().max(1)).max(1);
+        Parallel::new()
+            .min_chunk(Parallel::MIN_CHUNK / per_bank)
+            .map_reduce(
+                &banks.banks,
+                |bank| {
+                    self.select(bank.line)
+                        .map(|s| s.joltage)
+                        .unwrap_or_default()
+                },
+                |a, b| a + b,
+            )
+            .unwrap_or_default()
+    }
+}
+
+/// Largest number made of `elem_num` digits of `line` kept in order, in one pass.
+/// Ties go to the leftmost digits.
+fn select(line: &[u8], elem_num: usize) -> Selection {
+    Selection::new(line, monotonic(line, elem_num, |top, n| top < n))
+}
+
+// positions left on the stack when each digit pushes out the ones before it that it
+// `beats`, as long as enough digits are left to fill the selection
+fn monotonic(line: &[u8], elem_num: usize, beats: impl Fn(u8, u8) -> bool) -> Vec<usize> {
+    let mut drops = line.len().saturating_sub(elem_num);
+    let mut positions: Vec<usize> = Vec::with_capacity(line.len());
+    for (i, &n) in line.iter().enumerate() {
+        while drops > 0 && positions.last().is_some_and(|&top| beats(line[top], n)) {
+            positions.pop();
+            drops -= 1;
+        }
+        positions.push(i);
+    }
+    positions.truncate(elem_num);
+    positions
+}
+
+/// Picks the largest digit left to right, at least `gap` after the previous one and
+/// where `completes(state, pos, left)` says the `left` other picks can still follow.
+/// `state` is whatever the caller keeps about the picks so far, updated by `completes`.
+fn pick_largest<S: Copy>(
+    line: &[u8],
+    elem_num: usize,
+    gap: usize,
+    mut state: S,
+    completes: impl Fn(S, usize, usize) -> Option<S>,
+) -> Option<Vec<usize>> {
+    let mut picked = Vec::with_capacity(elem_num);
+    let mut from = 0;
+    for left in (0..elem_num).rev() {
+        // the last position with room for `left` more picks after it
+        let last = line.len().checked_sub(left * gap + 1)?;
+        let (pos, next) = (b'0'..=b'9').rev().find_map(|digit| {
+            // the leftmost copy, whatever follows a later one can follow it too
+            let pos = (from..=last).find(|&pos| line[pos] == digit)?;
+            completes(state, pos, left).map(|next| (pos, next))
+        })?;
+        picked.push(pos);
+        state = next;
+        from = pos + gap;
+    }
+    Some(picked)
+}
+
+// Largest multiple of `m`. A greedy scan can pick a big digit that leaves no way to
+// reach remainder 0, so `reach` first tells if `c` digits of `line[i..]` can bring
+// remainder `r` to 0, and the picks only go where that's possible.
+fn max_divisible(line: &[u8], elem_num: usize, m: u64) -> Option<Vec<usize>> {
+    let m = m as usize;
+    let digit = |i: usize| (line[i] - b'0') as usize;
+    // one bitset over `r` per `(i, c)`, all in a single buffer
+    let words = m.div_ceil(64);
+    let at = |i: usize, c: usize| (i * (elem_num + 1) + c) * words;
+    let mut reach = vec![0u64; (line.len() + 1) * (elem_num + 1) * words];
+    let get =
+        |reach: &[u64], i: usize, c: usize, r: usize| reach[at(i, c) + r / 64] >> (r % 64) & 1 == 1;
+    for i in 0..=line.len() {
+        reach[at(i, 0)] = 1;
+    }
+    for i in (0..line.len()).rev() {
+        // rows of `i` before the rows of `i + 1` they're computed from
+        let (rows, below) = reach.split_at_mut(at(i + 1, 0));
+        for c in 1..=elem_num {
+            let row = &mut rows[at(i, c)..][..words];
+            // skipping `line[i]` reaches whatever `line[i + 1..]` does
+            row.copy_from_slice(&below[c * words..][..words]);
+            let taken = &below[(c - 1) * words..][..words];
+            // `next` follows `(r * 10 + digit) % m` without a division per remainder
+            let mut next = digit(i) % m;
+            for r in 0..m {
+                row[r / 64] |= (taken[next / 64] >> (next % 64) & 1) << (r % 64);
+                next += 10;
+                while next >= m {
+                    next -= m;
+                }
+            }
+        }
+    }
+    if !get(&reach, 0, elem_num, 0) {
+        return None;
+    }
+    // the state is the remainder of the picks so far
+    pick_largest(line, elem_num, 1, 0, |r, pos, left| {
+        let r = (r * 10 + digit(pos)) % m;
+        get(&reach, pos + 1, left, r).then_some(r)
+    })
+}
+
+// Largest digit sum, then largest joltage. The best sum is easy (the largest digits),
+// but which copies to take to also get the largest joltage isn't, so `best[i][c]` holds
+// the largest sum of `c` digits of `line[i..]` and the picks only go where the total
+// can still be reached.
+fn max_digit_sum(line: &[u8], elem_num: usize) -> Option<Vec<usize>> {
+    let digit = |i: usize| (line[i] - b'0') as usize;
+    let mut best = vec![vec![None; elem_num + 1]; line.len() + 1];
+    for row in &mut best {
+        row[0] = Some(0);
+    }
+    for i in (0..line.len()).rev() {
+        for c in 1..=elem_num {
+            let take = best[i + 1][c - 1].map(|sum| sum + digit(i));
+            best[i][c] = best[i + 1][c].max(take);
+        }
+    }
+    let target = best[0][elem_num]?;
+    // the state is the digit sum of the picks so far
+    pick_largest(line, elem_num, 1, 0, |sum, pos, left| {
+        let sum = sum + digit(pos);
+        best[pos + 1][left]
+            .is_some_and(|rest| sum + rest == target)
+            .then_some(sum)
+    })
+}
+
+fn solve(banks: &Banks, config: Config) -> BigUint {
     let now = Instant::now();
-    let total = get_biggest_battery(buf, 2);
+    let total = config.total(banks);
     let elapsed = now.elapsed();
     println!("took: {:?}", elapsed);
     total
 }
 
-fn solve_part2(buf: &[u8]) -> u64 {
+fn solve_part1(banks: &Banks) -> BigUint {
+    solve(banks, Config::PART1)
+}
+
+fn solve_part2(banks: &Banks) -> BigUint {
+    solve(banks, Config::PART2)
+}
+
+// digit by digit version, rescanning what's left of the line for each digit, kept to
+// cross-check `select`
+fn greedy_part(banks: &Banks, elem_num: usize) -> BigUint {
     let now = Instant::now();
-    let total = get_biggest_battery(buf, 12);
+    let total = banks
+        .banks
+        .iter()
+        .map(|bank| biggest_battery(bank.line, elem_num))
+        .sum();
     let elapsed = now.elapsed();
     println!("took: {:?}", elapsed);
     total
 }
 
 #[inline(always)]
-fn get_biggest_battery(buf: &[u8], elem_num: usize) -> u64 {
-    let mut total = 0;
-    for line in buf.split(|&b| b == b'\n') {
-        if line.is_empty() {
-            continue;
-        }
-        let mut idx = 0;
-        for batt_idx in 0..elem_num {
-            let mut max = 0;
-            let inv = elem_num - batt_idx - 1;
-            for i in idx..line.len() - inv {
-                let n = line[i];
-                if n > max {
-                    max = n;
-                    idx = i + 1;
-                }
+fn biggest_battery(line: &[u8], elem_num: usize) -> BigUint {
+    let mut total = BigUint::zero();
+    if line.is_empty() {
+        return total;
+    }
+    let mut idx = 0;
+    for batt_idx in 0..elem_num {
+        let mut max = 0;
+        let inv = elem_num - batt_idx - 1;
+        for (i, &n) in line.iter().enumerate().take(line.len() - inv).skip(idx) {
+            if n > max {
+                max = n;
+                idx = i + 1;
             }
-            total += POW10[inv] * (max - b'0') as u64;
         }
+        total.push_digit(max - b'0');
     }
     total
 }
 
+/// How the chosen batteries stand out in a [`BankReport`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Highlight {
+    Brackets,
+    Ansi,
+}
+
+impl Highlight {
+    // `line` with the digits at `positions` marked
+    fn mark(self, line: &[u8], positions: &[usize]) -> String {
+        let (open, close) = match self {
+            Highlight::Brackets => ("[", "]"),
+            Highlight::Ansi => ("\x1b[1;32m", "\x1b[0m"),
+        };
+        let mut picked = positions.iter().peekable();
+        let mut marked = String::with_capacity(line.len() + positions.len() * 2);
+        for (i, &b) in line.iter().enumerate() {
+            let chosen = picked.next_if_eq(&&i).is_some();
+            if chosen {
+                marked.push_str(open);
+            }
+            marked.push(b as char);
+            if chosen {
+                marked.push_str(close);
+            }
+        }
+        marked
+    }
+}
+
+/// One bank of a [`BankReport`], `number` is its line in the input starting from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct BankEntry<'a> {
+    number: usize,
+    line: &'a [u8],
+    selection: Option<Selection>,
+}
+
+/// Every bank with the batteries a [`Config`] turns on in it and what they add to the
+/// total, to check the choices on real inputs.
+#[derive(Debug)]
+struct BankReport<'a> {
+    entries: Vec<BankEntry<'a>>,
+    total: BigUint,
+    highlight: Highlight,
+}
+
+impl<'a> BankReport<'a> {
+    pub fn new(banks: &Banks<'a>, config: Config, highlight: Highlight) -> Self {
+        let entries: Vec<_> = banks
+            .banks
+            .iter()
+            .map(|bank| BankEntry {
+                number: bank.number,
+                line: bank.line,
+                selection: config.select(bank.line),
+            })
+            .collect();
+        let total = entries
+            .iter()
+            .filter_map(|entry| entry.selection.as_ref())
+            .fold(BigUint::zero(), |mut total, selection| {
+                total += &selection.joltage;
+                total
+            });
+        Self {
+            entries,
+            total,
+            highlight,
+        }
+    }
+
+    // percentage of the total a joltage makes
+    fn share(&self, joltage: &BigUint) -> f64 {
+        match self.total.is_zero() {
+            true => 0.0,
+            false => 100.0 * joltage.to_f64() / self.total.to_f64(),
+        }
+    }
+
+    /// One row per bank with a selection, the positions (from 1, like the line numbers)
+    /// separated by spaces.
+    pub fn write_csv(&self, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "line,joltage,positions,share")?;
+        for entry in &self.entries {
+            let Some(selection) = &entry.selection else {
+                continue;
+            };
+            let positions: Vec<_> = selection
+                .positions
+                .iter()
+                .map(|pos| (pos + 1).to_string())
+                .collect();
+            writeln!(
+                out,
+                "{},{},{},{:.4}",
+                entry.number,
+                selection.joltage,
+                positions.join(" "),
+                self.share(&selection.joltage)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for BankReport<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.entries {
+            let Some(selection) = &entry.selection else {
+                let line = String::from_utf8_lossy(entry.line);
+                writeln!(f, "{:>5}: {line} has no selection", entry.number)?;
+                continue;
+            };
+            writeln!(
+                f,
+                "{:>5}: {} = {} ({:.2}%)",
+                entry.number,
+                self.highlight.mark(entry.line, &selection.positions),
+                selection.joltage,
+                self.share(&selection.joltage)
+            )?;
+        }
+        writeln!(f, "total: {}", self.total)
+    }
+}
+
 fn main() {
     let mut buf = Vec::new();
     File::open("inputs/day03.txt")
@@ -63,27 +496,317 @@ fn main() {
         .read_to_end(&mut buf)
         .unwrap();
 
-    let s = String::from_utf8(buf).unwrap();
-    let s = s.replace("\r\n", "\n");
-    let buf = s.into_bytes();
+    // `--greedy` to also run the digit by digit scan,
+    // `--digits K` for the total with K batteries per bank (12 if only an objective is
+    // given), up to the length of a bank, and `--min`, `--non-adjacent`,
+    // `--divisible M` (M up to 10000) or `--digit-sum` to choose them some other way,
+    // `--report FILE` to list the choice in every bank as CSV (`-` prints them with the
+    // batteries in brackets, or in color with `--color`),
+    // `--skip-invalid` to leave out bad banks with a warning instead of stopping
+    let (mut greedy, mut digits, mut objective) = (false, None, None);
+    let mut policy = Policy::Error;
+    let (mut report, mut highlight) = (None, Highlight::Brackets);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().expect("missing value after option");
+        match arg.as_str() {
+            "--greedy" => greedy = true,
+            "--digits" => digits = Some(value().parse().unwrap()),
+            "--min" => objective = Some(Objective::Min),
+            "--non-adjacent" => objective = Some(Objective::MaxNonAdjacent),
+            "--divisible" => objective = Some(Objective::MaxDivisible(value().parse().unwrap())),
+            "--digit-sum" => objective = Some(Objective::MaxDigitSum),
+            "--report" => report = Some(value()),
+            "--color" => highlight = Highlight::Ansi,
+            "--skip-invalid" => policy = Policy::Skip,
+            _ => panic!(
+                "usage: day03 [--greedy] [--digits K] \
+                 [--min | --non-adjacent | --divisible M | --digit-sum] [--report FILE] [--color] \
+                 [--skip-invalid]"
+            ),
+        }
+    }
+
+    let config = Config::new(digits.unwrap_or(12), objective.unwrap_or(Objective::Max));
+    // every bank needs enough batteries for all the answers asked for
+    let min_len = Config::PART2.digits.max(config.digits);
+    let banks = match Banks::parse(&buf, min_len, policy) {
+        Ok(banks) => banks,
+        Err(err) => {
+            eprintln!("inputs/day03.txt: {err}");
+            std::process::exit(1);
+        }
+    };
+    for err in &banks.skipped {
+        eprintln!("warning: inputs/day03.txt: {err}, bank skipped");
+    }
 
-    println!("Part 1: {}", solve_part1(&buf));
-    println!("Part 2: {}", solve_part2(&buf));
+    println!("Part 1: {}", solve_part1(&banks));
+    println!("Part 2: {}", solve_part2(&banks));
+    if greedy {
+        println!("Part 1 (greedy): {}", greedy_part(&banks, 2));
+        println!("Part 2 (greedy): {}", greedy_part(&banks, 12));
+    }
+    if digits.is_some() || objective.is_some() {
+        println!("{config:?}: {}", solve(&banks, config));
+        if greedy && config.objective == Objective::Max {
+            let total = greedy_part(&banks, config.digits);
+            println!("{config:?} (greedy): {total}");
+        }
+    }
+    if let Some(path) = report {
+        let report = BankReport::new(&banks, config, highlight);
+        match path.as_str() {
+            "-" => print!("{report}"),
+            path => report
+                .write_csv(BufWriter::new(File::create(path).unwrap()))
+                .unwrap(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{solve_part1, solve_part2};
-    const DATA: &'static str = "987654321111111\n811111111111119\n234234234234278\n818181911112111";
+    use crate::{
+        BankReport, Banks, Config, Highlight, Objective, ParseError, ParseErrorKind, Policy,
+        Selection, biggest_battery, greedy_part, select, solve, solve_part1, solve_part2,
+    };
+    use advent_of_code::utils::bignum::BigUint;
+    use advent_of_code::utils::rng::XorShift64;
+    const DATA: &str = "987654321111111\n811111111111119\n234234234234278\n818181911112111";
+
+    // every non-empty line, as long as it's all digits
+    fn banks(input: &[u8]) -> Banks<'_> {
+        Banks::parse(input, 0, Policy::Error).unwrap()
+    }
+
     #[test]
     fn test1() {
-        let result = solve_part1(DATA.as_bytes());
-        assert_eq!(result, 357);
+        let result = solve_part1(&banks(DATA.as_bytes()));
+        assert_eq!(result.to_u128(), Some(357));
     }
 
     #[test]
     fn test2() {
-        let result = solve_part2(DATA.as_bytes());
-        assert_eq!(result, 3121910778619);
+        let result = solve_part2(&banks(DATA.as_bytes()));
+        assert_eq!(result.to_u128(), Some(3121910778619));
+    }
+
+    #[test]
+    fn test_select() {
+        assert_eq!(
+            select(b"818181911112111", 2),
+            Selection {
+                positions: vec![6, 11],
+                joltage: BigUint::from(92u64)
+            }
+        );
+        let joltage = select(b"234234234234278", 12).joltage;
+        assert_eq!(joltage.to_u128(), Some(434234234278));
+        assert_eq!(
+            greedy_part(&banks(DATA.as_bytes()), 12),
+            solve_part2(&banks(DATA.as_bytes()))
+        );
+
+        let mut rng = XorShift64::new(0x03);
+        for _ in 0..500 {
+            let len = rng.range(1..=50) as usize;
+            // few distinct digits so there are plenty of ties
+            let digits = rng.range(1..=10);
+            let line: Vec<_> = (0..len).map(|_| b'0' + rng.below(digits) as u8).collect();
+            for elem_num in 1..=len {
+                let selection = select(&line, elem_num);
+                assert_eq!(selection.joltage, biggest_battery(&line, elem_num));
+                assert_eq!(selection.positions.len(), elem_num);
+                assert!(selection.positions.is_sorted_by(|a, b| a < b));
+                let picked: Vec<_> = selection.positions.iter().map(|&i| line[i]).collect();
+                assert_eq!(BigUint::from_decimal(&picked).unwrap(), selection.joltage);
+            }
+        }
+    }
+
+    #[test]
+    fn test_long_banks() {
+        // 13 digits and more used to be out of reach, 40 don't fit in 128 bits
+        let bank = "9876543210".repeat(5);
+        let whole = select(bank.as_bytes(), 50);
+        assert_eq!(whole.joltage.to_string(), bank);
+        assert_eq!(whole.joltage.to_u128(), None);
+        let config = Config::new(40, Objective::Max);
+        let total = solve(&banks(format!("{bank}\n{bank}\n").as_bytes()), config);
+        // the rest of the first block goes for a second 9 in front, then the next 0
+        let best = b"9987654321987654321098765432109876543210";
+        let expected = BigUint::from_decimal(best).unwrap();
+        assert_eq!(total, expected.clone() + expected);
+        let config = Config::new(13, Objective::Max);
+        assert_eq!(
+            solve(&banks(DATA.as_bytes()), config).to_u128(),
+            Some(31103799668619)
+        );
+    }
+
+    // best joltage of `config` trying every choice of batteries
+    fn brute_force(line: &[u8], config: Config) -> Option<u128> {
+        let digit = |i: usize| (line[i] - b'0') as u128;
+        (0u32..1 << line.len())
+            .filter(|mask| mask.count_ones() as usize == config.digits)
+            .filter(|mask| match config.objective {
+                Objective::MaxNonAdjacent => mask & (mask >> 1) == 0,
+                _ => true,
+            })
+            .map(|mask| {
+                let picked = (0..line.len()).filter(|i| mask >> i & 1 == 1);
+                let value = picked.clone().fold(0, |acc, i| acc * 10 + digit(i));
+                let sum: u128 = picked.map(digit).sum();
+                (value, sum)
+            })
+            .filter(|&(value, _)| match config.objective {
+                Objective::MaxDivisible(m) => value % m as u128 == 0,
+                _ => true,
+            })
+            .max_by_key(|&(value, sum)| match config.objective {
+                Objective::Min => (0, u128::MAX - value),
+                Objective::MaxDigitSum => (sum, value),
+                _ => (0, value),
+            })
+            .map(|(value, _)| value)
+    }
+
+    #[test]
+    fn test_objectives() {
+        let config = |objective| Config::new(3, objective);
+        let line = b"4819372";
+        let joltage = |objective| {
+            let selection = config(objective).select(line)?;
+            selection.joltage.to_u128()
+        };
+        assert_eq!(joltage(Objective::Max), Some(972));
+        assert_eq!(joltage(Objective::Min), Some(132));
+        assert_eq!(joltage(Objective::MaxNonAdjacent), Some(897));
+        assert_eq!(joltage(Objective::MaxDivisible(7)), Some(819));
+        assert_eq!(joltage(Objective::MaxDigitSum), Some(897));
+        assert_eq!(config(Objective::MaxNonAdjacent).select(b"9999"), None);
+        assert_eq!(config(Objective::MaxDivisible(5)).select(b"1234"), None);
+        let divisible = Config::new(2, Objective::MaxDivisible(3))
+            .select(b"9812")
+            .unwrap();
+        assert_eq!(divisible.positions, vec![1, 2]);
+        // more than one word of remainders
+        let wide = Config::new(5, Objective::MaxDivisible(131));
+        let selection = wide.select(b"98172635445362718").unwrap();
+        assert_eq!(selection.joltage.to_u128(), Some(98643));
+        let widest = Config::new(5, Objective::MaxDivisible(Config::MAX_DIVISOR));
+        let selection = widest.select(b"9870000").unwrap();
+        assert_eq!(selection.joltage.to_u128(), Some(90000));
+
+        let mut rng = XorShift64::new(0x48);
+        for _ in 0..300 {
+            let len = rng.range(1..=12) as usize;
+            let digits = rng.range(2..=10);
+            let line: Vec<_> = (0..len).map(|_| b'0' + rng.below(digits) as u8).collect();
+            let m = rng.range(1..=30);
+            let objectives = [
+                Objective::Max,
+                Objective::Min,
+                Objective::MaxNonAdjacent,
+                Objective::MaxDivisible(m),
+                Objective::MaxDigitSum,
+            ];
+            for objective in objectives {
+                let config = Config::new(rng.range(0..=len as u64) as usize, objective);
+                let selection = config.select(&line);
+                let joltage = selection.as_ref().map(|s| s.joltage.to_u128().unwrap());
+                assert_eq!(joltage, brute_force(&line, config), "{config:?} {line:?}");
+                if let Some(selection) = selection {
+                    assert_eq!(selection.positions.len(), config.digits);
+                    let gap = if objective == Objective::MaxNonAdjacent {
+                        2
+                    } else {
+                        1
+                    };
+                    assert!(selection.positions.windows(2).all(|w| w[1] >= w[0] + gap));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "divisor above 10000")]
+    fn test_divisor_limit() {
+        Config::new(2, Objective::MaxDivisible(Config::MAX_DIVISOR + 1));
+    }
+
+    #[test]
+    fn test_report() {
+        let input = b"987654321111111\n\n811111111111119\n234234234234278\n11\n";
+        let config = Config::new(3, Objective::Max);
+        let report = BankReport::new(&banks(input), config, Highlight::Brackets);
+        let numbers: Vec<_> = report.entries.iter().map(|entry| entry.number).collect();
+        assert_eq!(numbers, vec![1, 3, 4, 5]);
+        assert_eq!(report.total, solve(&banks(input), config));
+        assert_eq!(report.entries[3].selection, None);
+
+        let table = report.to_string();
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines[0], "    1: [9][8][7]654321111111 = 987 (43.21%)");
+        assert_eq!(lines[1], "    3: [8][1]111111111111[9] = 819 (35.86%)");
+        assert_eq!(lines[3], "    5: 11 has no selection");
+        assert_eq!(lines[4], "total: 2284");
+
+        let mut csv = Vec::new();
+        report.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "line,joltage,positions,share");
+        assert_eq!(lines[3], "4,478,3 14 15,20.9282");
+
+        let ansi = Highlight::Ansi.mark(b"1234", &[1, 3]);
+        assert_eq!(ansi, "1\x1b[1;32m2\x1b[0m3\x1b[1;32m4\x1b[0m");
+    }
+
+    #[test]
+    fn test_validation() {
+        let input = b"98765\n\n81x11\n234\n9\xc3\xa9\n";
+        let error = |line, column, kind| ParseError { line, column, kind };
+        let short = ParseErrorKind::TooShort { len: 3, needed: 4 };
+        assert_eq!(
+            Banks::parse(input, 4, Policy::Error),
+            Err(error(3, 3, ParseErrorKind::NotADigit(b'x')))
+        );
+        let banks = Banks::parse(input, 4, Policy::Skip).unwrap();
+        assert_eq!(banks.banks.len(), 1);
+        assert_eq!(
+            (banks.banks[0].number, banks.banks[0].line),
+            (1, &b"98765"[..])
+        );
+        assert_eq!(
+            banks.skipped,
+            vec![
+                error(3, 3, ParseErrorKind::NotADigit(b'x')),
+                error(4, 4, short),
+                error(5, 2, ParseErrorKind::NotADigit(0xc3)),
+            ]
+        );
+        assert_eq!(
+            banks.skipped[1].to_string(),
+            "line 4, column 4: bank has 3 batteries, 4 are needed"
+        );
+        assert_eq!(
+            banks.skipped[0].to_string(),
+            "line 3, column 3: 'x' isn't a battery"
+        );
+        assert_eq!(
+            error(1, 1, ParseErrorKind::NotADigit(0xff)).to_string(),
+            "line 1, column 1: '\\xff' isn't a battery"
+        );
+        assert_eq!(solve_part1(&banks).to_u128(), Some(98));
+
+        let banks = Banks::parse(b"12\r\n345\r\n", 2, Policy::Error).unwrap();
+        assert_eq!(solve_part1(&banks).to_u128(), Some(12 + 45));
+        assert_eq!(
+            Banks::parse(b"12\n345\n", 3, Policy::Error),
+            Err(error(1, 3, ParseErrorKind::TooShort { len: 2, needed: 3 }))
+        );
     }
 }
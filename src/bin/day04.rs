@@ -1,96 +1,51 @@
-use advent_of_code::utils::LineIterator;
+use advent_of_code::utils::grid::Grid;
 use std::fs::File;
 use std::io::Read;
 use std::time::Instant;
 
 const EMPTY: u8 = 255;
 
-const OFFSETS: [(i32, i32); 8] = [
-    (-1, -1),
-    (-1, 0),
-    (-1, 1),
-    (0, -1),
-    (0, 1),
-    (1, -1),
-    (1, 0),
-    (1, 1),
-];
-
-struct Grid {
-    data: Vec<u8>,
-    cols: usize,
-    rows: usize,
+fn parse(input: &[u8]) -> Grid<u8> {
+    Grid::from_bytes(input, |b| if b == b'@' { 0 } else { EMPTY })
 }
 
-impl Grid {
-    pub fn new(input: &[u8]) -> Self {
-        let mut lines_iter = LineIterator::new(input)
-            .filter(|line| !line.is_empty())
-            .peekable();
-        let cols = lines_iter.peek().map(|line| line.len()).unwrap_or(0);
-        let rows = lines_iter.clone().count();
-        let mut data = Vec::with_capacity(rows * cols);
-        for byte in input {
-            match byte {
-                b'.' => data.push(EMPTY),
-                b'@' => data.push(0),
-                _ => {}
+#[inline(always)]
+fn refresh_neighbor(grid: &mut Grid<u8>) {
+    for row in 0..grid.rows() {
+        for col in 0..grid.cols() {
+            if grid[(row, col)] == EMPTY {
+                continue;
             }
-        }
-
-        Self { data, cols, rows }
-    }
-
-    #[inline(always)]
-    fn refresh_neighbor(&mut self) {
-        for row in 0..self.rows {
-            for col in 0..self.cols {
-                let c = &self.data[row * self.cols + col];
-                if *c == EMPTY {
-                    continue;
-                }
 
-                self.process_row(row as i32, col as i32);
-            }
+            process_cell(grid, row, col);
         }
     }
+}
 
-    #[inline(always)]
-    fn refresh_neighbor_active(&mut self, active: &[usize]) {
-        for &idx in active {
-            let row = (idx / self.cols) as i32;
-            let col = (idx % self.cols) as i32;
-
-            self.process_row(row, col)
-        }
+#[inline(always)]
+fn refresh_neighbor_active(grid: &mut Grid<u8>, active: &[usize]) {
+    for &idx in active {
+        let (row, col) = grid.pos(idx);
+        process_cell(grid, row, col)
     }
+}
 
-    #[inline(always)]
-    fn process_row(&mut self, row: i32, col: i32) {
-        for &(offset_row, offset_col) in &OFFSETS {
-            let neighbor_row = row  + offset_row;
-            let neighbor_col = col  + offset_col;
-
-            if neighbor_row >= 0
-                && neighbor_row < self.rows as i32
-                && neighbor_col >= 0
-                && neighbor_col < self.cols as i32
-            {
-                let idx = neighbor_row as usize * self.cols + neighbor_col as usize;
-                if self.data[idx] != EMPTY {
-                    self.data[idx] += 1;
-                }
-            }
+#[inline(always)]
+fn process_cell(grid: &mut Grid<u8>, row: usize, col: usize) {
+    for neighbor in grid.neighbors8(row, col) {
+        let cell = &mut grid[neighbor];
+        if *cell != EMPTY {
+            *cell += 1;
         }
     }
 }
 
 fn solve_part1(buf: &[u8]) -> u32 {
     let now = Instant::now();
-    let mut grid = Grid::new(buf);
-    grid.refresh_neighbor();
+    let mut grid = parse(buf);
+    refresh_neighbor(&mut grid);
     let result = grid
-        .data
+        .as_slice()
         .iter()
         .filter(|&&cell| cell < 4 && cell != EMPTY)
         .count() as u32;
@@ -101,9 +56,9 @@ fn solve_part1(buf: &[u8]) -> u32 {
 
 fn solve_part2(buf: &[u8]) -> u32 {
     let now = Instant::now();
-    let mut grid = Grid::new(buf);
+    let mut grid = parse(buf);
     let mut active: Vec<usize> = grid
-        .data
+        .as_slice()
         .iter()
         .enumerate()
         .filter(|(_, c)| **c != EMPTY)
@@ -111,15 +66,16 @@ fn solve_part2(buf: &[u8]) -> u32 {
         .collect();
     let mut result = 0;
     loop {
-        grid.refresh_neighbor_active(&active);
+        refresh_neighbor_active(&mut grid, &active);
         let prev_len = active.len();
+        let cells = grid.as_mut_slice();
         active.retain(|&idx| {
-            if grid.data[idx] < 4 {
+            if cells[idx] < 4 {
                 result += 1;
-                grid.data[idx] = EMPTY;
+                cells[idx] = EMPTY;
                 false
             } else {
-                grid.data[idx] = 0;
+                cells[idx] = 0;
                 true
             }
         });
@@ -1,3 +1,4 @@
+use advent_of_code::utils::columns::Columns;
 use advent_of_code::utils::parse_u64;
 use std::fs::File;
 use std::io::Read;
@@ -41,6 +42,14 @@ impl Operation {
         }
     }
 
+    pub fn from_byte(c: u8) -> Operation {
+        match c {
+            b'*' => Operation::Mul(1),
+            b'+' => Operation::Add(0),
+            _ => unreachable!(),
+        }
+    }
+
     pub fn apply(&mut self, number: u64) {
         match self {
             Operation::Mul(val) => *val *= number,
@@ -50,6 +59,8 @@ impl Operation {
 }
 
 impl std::iter::Sum<Operation> for u64 {
+    // `iter.sum()` would recurse into this impl
+    #[allow(clippy::unnecessary_fold)]
     fn sum<I: Iterator<Item = Operation>>(iter: I) -> u64 {
         iter.fold(0, |acc, op| acc + op)
     }
@@ -91,42 +102,36 @@ fn solve_part1(input: &str) -> u64 {
 
 fn solve_part2(input: &str) -> u64 {
     let now = Instant::now();
-    let lines_size = input.lines().next().unwrap().as_bytes().len();
-    let mut is_mul = false;
+    let columns = Columns::new(input.as_bytes());
+    let ops_row = columns.height() - 1;
     let mut total = 0;
-    let mut operation_total = 0; // 1 when multipliying since if we use 0 it stay at 0
-    for col in 0..lines_size {
-        let mut num = NumBuilder::new();
-
-        for line in input.lines().rev() {
-            let c = line.as_bytes()[col];
-            match c {
-                32 => continue,
-                42 => {
-                    total += operation_total;
-                    is_mul = true;
-                    operation_total = 1;
-                }
-                43 => {
-                    total += operation_total;
-                    is_mul = false;
-                    operation_total = 0;
+    for block in columns.blocks() {
+        // the operator sits somewhere under its block on the last line
+        let mut operation = block
+            .clone()
+            .map(|col| columns.get(ops_row, col))
+            .find(|&c| c != b' ')
+            .map(Operation::from_byte)
+            .unwrap();
+
+        for col in block {
+            let mut num = NumBuilder::new();
+            // bottom-up so the digits come least significant first, skipping the operator line
+            for c in columns.column(col).rev().skip(1) {
+                match c {
+                    b' ' => continue,
+                    b'0'..=b'9' => num.add(c),
+                    _ => unreachable!(),
                 }
-                48..=57 => num.add(c),
-                _ => unreachable!(),
             }
+            let num = num.take_val();
+            if num == 0 {
+                continue;
+            }
+            operation.apply(num);
         }
-        let num = num.take_val();
-        if num == 0 {
-            continue;
-        }
-        if is_mul {
-            operation_total *= num;
-        } else {
-            operation_total += num;
-        }
+        total = total + operation;
     }
-    total += operation_total;
     let elapsed = now.elapsed();
     println!("took: {:?}", elapsed);
     total
@@ -150,8 +155,7 @@ fn main() {
 mod tests {
     use crate::{solve_part1, solve_part2};
 
-    const INPUT: &'static str =
-        "123 328  51 64 \n 45 64  387 23 \n  6 98  215 314\n*   +   *   +  ";
+    const INPUT: &str = "123 328  51 64 \n 45 64  387 23 \n  6 98  215 314\n*   +   *   +  ";
     #[test]
     fn test_solve_part1() {
         let answer = solve_part1(INPUT);
@@ -163,4 +167,15 @@ mod tests {
         let answer = solve_part2(INPUT);
         assert_eq!(answer, 3263827);
     }
+
+    #[test]
+    fn test_solve_part2_trimmed() {
+        let trimmed = INPUT
+            .lines()
+            .map(|line| line.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let answer = solve_part2(&trimmed);
+        assert_eq!(answer, 3263827);
+    }
 }
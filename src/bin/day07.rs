@@ -1,5 +1,6 @@
 use std::{fs::File, io::Read, time::Instant};
 
+use advent_of_code::utils::search::{Graph, count_paths};
 use advent_of_code::utils::{BitSet, LineIterator};
 
 // generic to make it work with the test :/
@@ -143,6 +144,53 @@ fn solve_part2<const WIDTH: usize, const BITFIELD_CNT: usize>(buf: &[u8]) -> usi
     total
 }
 
+/// The manifold as a DAG, a node is a beam entering splitter line `row` at `col`.
+/// Beams that make it past the last line end on `(rows.len(), col)`.
+struct Manifold<'a> {
+    rows: Vec<&'a [u8]>,
+}
+
+impl Graph for Manifold<'_> {
+    type Node = (usize, usize);
+
+    fn edges(&self, (row, col): (usize, usize)) -> impl Iterator<Item = ((usize, usize), u64)> {
+        let line = self.rows.get(row).copied().unwrap_or_default();
+        let next: [Option<usize>; 2] = match line.get(col) {
+            Some(b'^') => [
+                col.checked_sub(1),
+                Some(col + 1).filter(|&c| c < line.len()),
+            ],
+            Some(_) => [Some(col), None],
+            // beam left the manifold or we're already past the last line
+            None => [None, None],
+        };
+        next.into_iter()
+            .flatten()
+            .map(move |col| ((row + 1, col), 1))
+    }
+}
+
+// part 2 again, but as counting the paths through the beam DAG
+fn solve_part2_dag(buf: &[u8]) -> u64 {
+    let now = Instant::now();
+    let mut lines = LineIterator::new(buf);
+    let start_pos = match lines
+        .next()
+        .and_then(|line| line.iter().position(|&c| c == b'S'))
+    {
+        Some(start) => start,
+        None => return 0,
+    };
+    let manifold = Manifold {
+        rows: lines.skip(1).step_by(2).collect(),
+    };
+    let exit = manifold.rows.len();
+    let total = count_paths(&manifold, (0, start_pos), |(row, _)| row == exit).unwrap();
+    let elapsed = now.elapsed();
+    println!("took: {:?}", elapsed);
+    total
+}
+
 fn main() {
     let mut buf = Vec::new();
     File::open("inputs/day07.txt")
@@ -153,6 +201,7 @@ fn main() {
     println!("Part 1: {}", solve_part1(&buf));
     println!("Part 1v2: {}", solve_part1_2::<141, 3>(&buf));
     println!("Part 2: {}", solve_part2::<141, 3>(&buf));
+    println!("Part 2 (dag): {}", solve_part2_dag(&buf));
     //println!("Part 2: {}", solve_part2(&s));
 }
 
@@ -165,9 +214,9 @@ fn push_unique(deq: &mut Vec<usize>, val: usize) {
 
 #[cfg(test)]
 mod tests {
-    use crate::{solve_part1, solve_part1_2, solve_part2};
+    use crate::{solve_part1, solve_part1_2, solve_part2, solve_part2_dag};
 
-    const INPUT: &'static str = ".......S.......\n\
+    const INPUT: &str = ".......S.......\n\
     ...............\n\
     .......^.......\n\
     ...............\n\
@@ -202,4 +251,10 @@ mod tests {
         let res = solve_part2::<15, 1>(INPUT.as_bytes());
         assert_eq!(res, 40);
     }
+
+    #[test]
+    fn part2_dag() {
+        let res = solve_part2_dag(INPUT.as_bytes());
+        assert_eq!(res, 40);
+    }
 }
@@ -1,3 +1,13 @@
+pub mod bignum;
+pub mod columns;
+pub mod grid;
+pub mod parallel;
+pub mod point;
+pub mod rank;
+pub mod ranges;
+pub mod rng;
+pub mod search;
+
 use std::{
     fmt::Display,
     ops::{BitAnd, BitOr, Not},
@@ -72,7 +82,7 @@ pub struct BitSet<const WIDTH: usize, const N: usize>([u64; N]);
 // googled this part and converted to rust lol
 impl<const WIDTH: usize, const N: usize> BitSet<WIDTH, N> {
     const LAST_IDX: usize = N - 1;
-    const LAST_MASK: u64 = if WIDTH % 64 == 0 {
+    const LAST_MASK: u64 = if WIDTH.is_multiple_of(64) {
         u64::MAX
     } else {
         (1u64 << (WIDTH % 64)) - 1
@@ -189,7 +199,7 @@ impl<const WIDTH: usize, const N: usize> Display for BitSet<WIDTH, N> {
 mod tests {
     use crate::utils::LineIterator;
 
-    const RESULT: [&'static [u8]; 4] = [
+    const RESULT: [&[u8]; 4] = [
         b"987654321111111",
         b"811111111111119",
         b"234234234234278",
@@ -197,7 +207,7 @@ mod tests {
     ];
     #[test]
     fn test_unix() {
-        const DATA: &'static str =
+        const DATA: &str =
             "987654321111111\n811111111111119\n234234234234278\n818181911112111";
         let iter = LineIterator::new(DATA.as_bytes());
         assert_eq!(&RESULT, iter.collect::<Vec<_>>().as_slice());
@@ -205,7 +215,7 @@ mod tests {
 
     #[test]
     fn test_windows() {
-        const DATA: &'static str =
+        const DATA: &str =
             "987654321111111\r\n811111111111119\r\n234234234234278\r\n818181911112111";
         let iter = LineIterator::new(DATA.as_bytes());
         assert_eq!(&RESULT, iter.collect::<Vec<_>>().as_slice());
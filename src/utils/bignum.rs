@@ -0,0 +1,214 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, AddAssign},
+};
+
+// one limb holds 18 decimal digits, so printing is just printing the limbs
+const LIMB: u64 = 1_000_000_000_000_000_000;
+
+/// Unsigned integer of any size, for the answers that outgrow `u128`.
+/// Stored as base 10^18 limbs, least significant first, without leading zero limbs
+/// (zero has none).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    #[inline]
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// `self * mul + add`.
+    pub fn mul_add(&mut self, mul: u64, add: u64) {
+        let mut carry = add as u128;
+        for limb in &mut self.limbs {
+            let value = *limb as u128 * mul as u128 + carry;
+            *limb = (value % LIMB as u128) as u64;
+            carry = value / LIMB as u128;
+        }
+        while carry > 0 {
+            self.limbs.push((carry % LIMB as u128) as u64);
+            carry /= LIMB as u128;
+        }
+        self.trim();
+    }
+
+    /// Appends a decimal digit, `self * 10 + digit`.
+    #[inline]
+    pub fn push_digit(&mut self, digit: u8) {
+        debug_assert!(digit < 10);
+        self.mul_add(10, digit as u64);
+    }
+
+    /// Parses ASCII decimal digits, `None` if there are none or one isn't a digit.
+    pub fn from_decimal(digits: &[u8]) -> Option<Self> {
+        if digits.is_empty() {
+            return None;
+        }
+        let mut n = Self::zero();
+        for &b in digits {
+            if !b.is_ascii_digit() {
+                return None;
+            }
+            n.push_digit(b - b'0');
+        }
+        Some(n)
+    }
+
+    /// The value as a `u128`, `None` if it doesn't fit.
+    pub fn to_u128(&self) -> Option<u128> {
+        self.limbs.iter().rev().try_fold(0u128, |acc, &limb| {
+            acc.checked_mul(LIMB as u128)?.checked_add(limb as u128)
+        })
+    }
+
+    /// Closest `f64`, for ratios and other rough uses.
+    pub fn to_f64(&self) -> f64 {
+        self.limbs
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &limb| acc * LIMB as f64 + limb as f64)
+    }
+
+    // drops the leading zero limbs
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(mut n: u128) -> Self {
+        let mut limbs = Vec::new();
+        while n > 0 {
+            limbs.push((n % LIMB as u128) as u64);
+            n /= LIMB as u128;
+        }
+        Self { limbs }
+    }
+}
+
+impl From<u64> for BigUint {
+    #[inline]
+    fn from(n: u64) -> Self {
+        Self::from(n as u128)
+    }
+}
+
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, rhs: &BigUint) {
+        if self.limbs.len() < rhs.limbs.len() {
+            self.limbs.resize(rhs.limbs.len(), 0);
+        }
+        let mut carry = 0;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let sum = *limb + rhs.limbs.get(i).copied().unwrap_or(0) + carry;
+            (*limb, carry) = (sum % LIMB, sum / LIMB);
+            if carry == 0 && i >= rhs.limbs.len() {
+                break;
+            }
+        }
+        if carry > 0 {
+            self.limbs.push(carry);
+        }
+    }
+}
+
+impl Add for BigUint {
+    type Output = BigUint;
+
+    #[inline]
+    fn add(mut self, rhs: BigUint) -> BigUint {
+        self += &rhs;
+        self
+    }
+}
+
+impl std::iter::Sum for BigUint {
+    fn sum<I: Iterator<Item = BigUint>>(iter: I) -> Self {
+        iter.fold(Self::zero(), Add::add)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigUint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some((top, rest)) = self.limbs.split_last() else {
+            return write!(f, "0");
+        };
+        write!(f, "{top}")?;
+        for limb in rest.iter().rev() {
+            write!(f, "{limb:018}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::bignum::BigUint;
+    use crate::utils::rng::XorShift64;
+
+    #[test]
+    fn test_arithmetic() {
+        let mut rng = XorShift64::new(0xb16);
+        for _ in 0..1000 {
+            let (a, b) = (rng.next_u64() as u128, rng.next_u64() as u128);
+            let bits = rng.range(0..=40);
+            let (mul, add) = (rng.below(1 << bits), rng.next_u64());
+            let big = BigUint::from(a) + BigUint::from(b);
+            assert_eq!(big.to_u128(), Some(a + b));
+            assert_eq!(big.to_string(), (a + b).to_string());
+            let mut product = BigUint::from(a);
+            product.mul_add(mul, add);
+            assert_eq!(product.to_u128(), Some(a * mul as u128 + add as u128));
+            assert_eq!(a.cmp(&b), BigUint::from(a).cmp(&BigUint::from(b)));
+        }
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!(BigUint::from(0u64), BigUint::zero());
+        assert_eq!(BigUint::from(u128::MAX).to_u128(), Some(u128::MAX));
+    }
+
+    #[test]
+    fn test_wide() {
+        let digits = "123456789".repeat(9);
+        let n = BigUint::from_decimal(digits.as_bytes()).unwrap();
+        assert_eq!(n.to_string(), digits);
+        assert_eq!(n.to_u128(), None);
+        assert!(n > BigUint::from(u128::MAX));
+        let past = BigUint::from(u128::MAX) + BigUint::from(1u64);
+        assert_eq!(past.to_string(), "340282366920938463463374607431768211456");
+        assert_eq!(past.to_u128(), None);
+        assert_eq!(past.to_f64(), 2f64.powi(128));
+        let twice = n.clone() + n;
+        assert_eq!(twice.to_string(), "246913578".repeat(9));
+        let zeros = BigUint::from_decimal(b"000100000000000000000000").unwrap();
+        assert_eq!(zeros.to_string(), "100000000000000000000");
+        assert_eq!(BigUint::from_decimal(b""), None);
+        assert_eq!(BigUint::from_decimal(b"12a"), None);
+    }
+}
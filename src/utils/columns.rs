@@ -0,0 +1,95 @@
+use std::ops::Range;
+
+use crate::utils::LineIterator;
+
+/// Column-oriented view over text lines that don't all have the same length.
+/// Short lines behave as if they were padded with spaces up to the longest one,
+/// so trimmed trailing whitespace doesn't change the layout.
+#[derive(Clone)]
+pub struct Columns<'a> {
+    lines: Vec<&'a [u8]>,
+    width: usize,
+}
+
+impl<'a> Columns<'a> {
+    pub fn new(input: &'a [u8]) -> Self {
+        let lines: Vec<_> = LineIterator::new(input).collect();
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        Self { lines, width }
+    }
+
+    /// Length of the longest line.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Number of lines.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Byte at `(row, col)`, `b' '` past the end of a short line.
+    #[inline(always)]
+    pub fn get(&self, row: usize, col: usize) -> u8 {
+        self.lines[row].get(col).copied().unwrap_or(b' ')
+    }
+
+    /// Bytes of column `col` from the top line down, use `.rev()` to go bottom-up.
+    #[inline]
+    pub fn column(
+        &self,
+        col: usize,
+    ) -> impl DoubleEndedIterator<Item = u8> + ExactSizeIterator + '_ {
+        self.lines
+            .iter()
+            .map(move |line| line.get(col).copied().unwrap_or(b' '))
+    }
+
+    /// `true` when every line has a space (or nothing) at `col`.
+    #[inline]
+    pub fn is_blank(&self, col: usize) -> bool {
+        self.column(col).all(|c| c == b' ')
+    }
+
+    /// Ranges of columns separated by all-blank columns, left to right.
+    pub fn blocks(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        let mut col = 0;
+        std::iter::from_fn(move || {
+            while col < self.width && self.is_blank(col) {
+                col += 1;
+            }
+            if col >= self.width {
+                return None;
+            }
+            let start = col;
+            while col < self.width && !self.is_blank(col) {
+                col += 1;
+            }
+            Some(start..col)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::columns::Columns;
+
+    #[test]
+    fn test_ragged() {
+        let columns = Columns::new(b"12 4\r\n5\n 7 89");
+        assert_eq!((columns.width(), columns.height()), (5, 3));
+        assert_eq!(columns.column(1).collect::<Vec<_>>(), b"2 7");
+        assert_eq!(columns.column(4).rev().collect::<Vec<_>>(), b"9  ");
+        assert_eq!(columns.get(1, 3), b' ');
+        assert!(columns.is_blank(2));
+    }
+
+    #[test]
+    fn test_blocks() {
+        let columns = Columns::new(b"12  4\n5   67\n  \n");
+        assert_eq!(columns.blocks().collect::<Vec<_>>(), vec![0..2, 4..6]);
+        assert_eq!(Columns::new(b"").blocks().count(), 0);
+    }
+}
@@ -0,0 +1,348 @@
+use std::ops::{Index, IndexMut};
+
+use crate::utils::LineIterator;
+use crate::utils::point::{Dir4, Dir8, Point2};
+
+/// Row-major 2D grid stored in a single flat `Vec`, indexed with `(row, col)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    data: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from the non-empty lines of `input`, mapping every byte with `f`.
+    /// The width is taken from the first line, `\r\n` line endings are accepted.
+    pub fn from_bytes(input: &[u8], mut f: impl FnMut(u8) -> T) -> Self {
+        let mut lines = LineIterator::new(input)
+            .filter(|line| !line.is_empty())
+            .peekable();
+        let cols = lines.peek().map(|line| line.len()).unwrap_or(0);
+        let mut data = Vec::with_capacity(input.len());
+        let mut rows = 0;
+        for line in lines {
+            assert_eq!(line.len(), cols, "line {rows} has a different width");
+            data.extend(line.iter().map(|&b| f(b)));
+            rows += 1;
+        }
+
+        Self { data, rows, cols }
+    }
+
+    pub fn from_vec(data: Vec<T>, rows: usize, cols: usize) -> Self {
+        assert_eq!(data.len(), rows * cols, "data doesn't match the grid size");
+        Self { data, rows, cols }
+    }
+
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Flat index of `(row, col)` in [`Grid::as_slice`].
+    #[inline(always)]
+    pub fn idx(&self, row: usize, col: usize) -> usize {
+        row * self.cols + col
+    }
+
+    /// `(row, col)` of a flat index.
+    #[inline(always)]
+    pub fn pos(&self, idx: usize) -> (usize, usize) {
+        (idx / self.cols, idx % self.cols)
+    }
+
+    #[inline]
+    pub fn in_bounds(&self, row: usize, col: usize) -> bool {
+        row < self.rows && col < self.cols
+    }
+
+    #[inline]
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if self.in_bounds(row, col) {
+            Some(&self.data[self.idx(row, col)])
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        if self.in_bounds(row, col) {
+            let idx = self.idx(row, col);
+            Some(&mut self.data[idx])
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    #[inline]
+    pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+        &mut self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// A column isn't contiguous in memory so it's returned as a strided iterator.
+    #[inline]
+    pub fn col(&self, col: usize) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        assert!(col < self.cols, "column {col} out of bounds");
+        self.data.iter().skip(col).step_by(self.cols)
+    }
+
+    /// Iterates `((row, col), &cell)` in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        self.data
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| (self.pos(idx), cell))
+    }
+
+    #[inline]
+    pub fn get_point(&self, point: Point2) -> Option<&T> {
+        let (row, col) = point.to_index()?;
+        self.get(row, col)
+    }
+
+    #[inline]
+    pub fn get_point_mut(&mut self, point: Point2) -> Option<&mut T> {
+        let (row, col) = point.to_index()?;
+        self.get_mut(row, col)
+    }
+
+    /// Cell reached by moving `delta` from `(row, col)`, `None` when it leaves the grid.
+    #[inline(always)]
+    pub fn step(&self, row: usize, col: usize, delta: Point2) -> Option<(usize, usize)> {
+        step_in(self.rows, self.cols, row, col, delta)
+    }
+
+    /// Orthogonal neighbors of `(row, col)` that are inside the grid, clockwise from up.
+    #[inline]
+    pub fn neighbors4(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        let (rows, cols) = (self.rows, self.cols);
+        Dir4::ALL
+            .into_iter()
+            .filter_map(move |dir| step_in(rows, cols, row, col, dir.delta()))
+    }
+
+    /// Orthogonal and diagonal neighbors of `(row, col)` that are inside the grid,
+    /// clockwise from up.
+    #[inline]
+    pub fn neighbors8(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        let (rows, cols) = (self.rows, self.cols);
+        Dir8::ALL
+            .into_iter()
+            .filter_map(move |dir| step_in(rows, cols, row, col, dir.delta()))
+    }
+
+    /// Position of the first cell (row-major) equal to `value`.
+    pub fn find(&self, value: T) -> Option<(usize, usize)>
+    where
+        T: PartialEq,
+    {
+        self.data
+            .iter()
+            .position(|cell| *cell == value)
+            .map(|idx| self.pos(idx))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            data: self.data.iter().map(f).collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(rows: usize, cols: usize, fill: T) -> Self {
+        Self {
+            data: vec![fill; rows * cols],
+            rows,
+            cols,
+        }
+    }
+
+    /// Builds a `cols x rows` grid where `(row, col)` comes from `(col, row)` of `self`.
+    pub fn transpose(&self) -> Self {
+        self.remap(self.cols, self.rows, |row, col| (col, row))
+    }
+
+    /// Rotates a quarter turn clockwise.
+    pub fn rotate_cw(&self) -> Self {
+        let rows = self.rows;
+        self.remap(self.cols, self.rows, |row, col| (rows - 1 - col, row))
+    }
+
+    /// Rotates a quarter turn counter-clockwise.
+    pub fn rotate_ccw(&self) -> Self {
+        let cols = self.cols;
+        self.remap(self.cols, self.rows, |row, col| (col, cols - 1 - row))
+    }
+
+    /// Mirrors left to right.
+    pub fn flip_horizontal(&self) -> Self {
+        let cols = self.cols;
+        self.remap(self.rows, self.cols, |row, col| (row, cols - 1 - col))
+    }
+
+    /// Mirrors top to bottom.
+    pub fn flip_vertical(&self) -> Self {
+        let rows = self.rows;
+        self.remap(self.rows, self.cols, |row, col| (rows - 1 - row, col))
+    }
+
+    // build a new `rows x cols` grid, `src` gives where each new cell comes from in `self`
+    fn remap(
+        &self,
+        rows: usize,
+        cols: usize,
+        src: impl Fn(usize, usize) -> (usize, usize),
+    ) -> Self {
+        let mut data = Vec::with_capacity(rows * cols);
+        for row in 0..rows {
+            for col in 0..cols {
+                let (src_row, src_col) = src(row, col);
+                data.push(self.data[self.idx(src_row, src_col)].clone());
+            }
+        }
+        Self { data, rows, cols }
+    }
+}
+
+impl Grid<u8> {
+    /// Grid of the raw bytes of `input`.
+    pub fn from_ascii(input: &[u8]) -> Self {
+        Self::from_bytes(input, |b| b)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    #[inline(always)]
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        self.get(row, col).expect("position outside of the grid")
+    }
+}
+
+impl<T> Index<Point2> for Grid<T> {
+    type Output = T;
+
+    #[inline(always)]
+    fn index(&self, point: Point2) -> &Self::Output {
+        self.get_point(point).expect("point outside of the grid")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    #[inline(always)]
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        self.get_mut(row, col)
+            .expect("position outside of the grid")
+    }
+}
+
+impl<T> IndexMut<Point2> for Grid<T> {
+    #[inline(always)]
+    fn index_mut(&mut self, point: Point2) -> &mut Self::Output {
+        self.get_point_mut(point)
+            .expect("point outside of the grid")
+    }
+}
+
+// free function so the neighbor iterators don't borrow the grid
+#[inline(always)]
+fn step_in(
+    rows: usize,
+    cols: usize,
+    row: usize,
+    col: usize,
+    delta: Point2,
+) -> Option<(usize, usize)> {
+    let (row, col) = (Point2::from_index(row, col) + delta).to_index()?;
+    (row < rows && col < cols).then_some((row, col))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::grid::Grid;
+    use crate::utils::point::{Dir4, Dir8, Point2};
+
+    const DATA: &[u8] = b"abc\r\ndef\r\n";
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::from_ascii(DATA);
+        assert_eq!((grid.rows(), grid.cols()), (2, 3));
+        assert_eq!(grid[(1, 2)], b'f');
+        assert_eq!(grid.row(0), b"abc");
+        assert_eq!(grid.col(1).copied().collect::<Vec<_>>(), b"be");
+        assert_eq!(grid.get(2, 0), None);
+        assert_eq!(grid.find(b'e'), Some((1, 1)));
+        assert_eq!(grid.find(b'S'), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "position outside of the grid")]
+    fn test_index_past_row_end() {
+        let grid = Grid::from_ascii(DATA);
+        let _ = grid[(0, 3)];
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::from_ascii(DATA);
+        let corner: Vec<_> = grid.neighbors8(0, 0).collect();
+        assert_eq!(corner, vec![(0, 1), (1, 1), (1, 0)]);
+        let edge: Vec<_> = grid.neighbors4(1, 1).collect();
+        assert_eq!(edge, vec![(0, 1), (1, 2), (1, 0)]);
+        assert_eq!(grid.neighbors8(1, 1).count(), 5);
+        assert_eq!(grid.step(1, 1, Dir8::NE.delta()), Some((0, 2)));
+        assert_eq!(grid.step(1, 2, Dir4::Right.delta()), None);
+        assert_eq!(grid[Point2::new(2, 1)], b'f');
+        assert_eq!(grid.get_point(Point2::new(-1, 0)), None);
+    }
+
+    #[test]
+    fn test_transforms() {
+        let grid = Grid::from_ascii(DATA);
+        assert_eq!(grid.transpose().as_slice(), b"adbecf");
+        assert_eq!(grid.rotate_cw().as_slice(), b"daebfc");
+        assert_eq!(grid.rotate_ccw().as_slice(), b"cfbead");
+        assert_eq!(grid.flip_horizontal().as_slice(), b"cbafed");
+        assert_eq!(grid.flip_vertical().as_slice(), b"defabc");
+        assert_eq!(grid.rotate_cw().rotate_ccw(), grid);
+        assert_eq!(
+            grid.rotate_cw().rotate_cw(),
+            grid.flip_vertical().flip_horizontal()
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use std::thread;
+
+use crate::utils::LineIterator;
+
+/// Map-reduce over lines or slices on `std::thread::scope` workers.
+/// The work is split in one contiguous chunk per thread and the results are always
+/// combined left to right, so the output doesn't depend on scheduling or thread count
+/// (as long as `reduce` is associative). Inputs too small to be worth a thread stay serial,
+/// see [`Parallel::min_chunk`].
+#[derive(Clone, Copy, Debug)]
+pub struct Parallel {
+    threads: usize,
+    min_chunk: usize,
+}
+
+impl Default for Parallel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Parallel {
+    /// Default [`Parallel::min_chunk`], cheap work on fewer bytes takes less time than
+    /// starting a thread.
+    pub const MIN_CHUNK: usize = 16 * 1024;
+
+    /// One thread per available core.
+    pub fn new() -> Self {
+        let threads = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        Self {
+            threads,
+            min_chunk: Self::MIN_CHUNK,
+        }
+    }
+
+    pub fn with_threads(threads: usize) -> Self {
+        assert!(threads > 0, "need at least one thread");
+        Self {
+            threads,
+            min_chunk: Self::MIN_CHUNK,
+        }
+    }
+
+    /// Smallest chunk given to a thread, in bytes for the line methods and in items for
+    /// [`Parallel::map_reduce`]. Defaults to 16 KiB (or 16Ki items), lower it when every
+    /// item is a lot of work.
+    pub fn min_chunk(mut self, min_chunk: usize) -> Self {
+        self.min_chunk = min_chunk.max(1);
+        self
+    }
+
+    #[inline]
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Splits `input` in up to `threads` chunks of similar size (but not smaller than
+    /// `min_chunk`), each one ending right after a `\n` (except the last) so no line is cut
+    /// in half.
+    pub fn line_chunks<'a>(&self, input: &'a [u8]) -> Vec<&'a [u8]> {
+        let target = input.len().div_ceil(self.threads).max(self.min_chunk);
+        let mut chunks = Vec::with_capacity(self.threads);
+        let mut rest = input;
+        while !rest.is_empty() {
+            let end = if rest.len() <= target {
+                rest.len()
+            } else {
+                rest[target..]
+                    .iter()
+                    .position(|&b| b == b'\n')
+                    .map_or(rest.len(), |pos| target + pos + 1)
+            };
+            let (chunk, tail) = rest.split_at(end);
+            chunks.push(chunk);
+            rest = tail;
+        }
+        chunks
+    }
+
+    /// Runs `map` on every line of `input` and folds the results with `reduce`,
+    /// `None` when there's no line at all.
+    pub fn map_reduce_lines<T, M, R>(&self, input: &[u8], map: M, reduce: R) -> Option<T>
+    where
+        T: Send,
+        M: Fn(&[u8]) -> T + Sync,
+        R: Fn(T, T) -> T + Sync,
+    {
+        let chunks = self.line_chunks(input);
+        self.run(&chunks, |chunk| {
+            LineIterator::new(chunk).map(&map).reduce(&reduce)
+        })
+        .into_iter()
+        .flatten()
+        .reduce(&reduce)
+    }
+
+    /// Runs `map` on every line of `input`, results are in line order.
+    pub fn map_lines<T, M>(&self, input: &[u8], map: M) -> Vec<T>
+    where
+        T: Send,
+        M: Fn(&[u8]) -> T + Sync,
+    {
+        let chunks = self.line_chunks(input);
+        self.run(&chunks, |chunk| {
+            LineIterator::new(chunk).map(&map).collect::<Vec<_>>()
+        })
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Same as [`Parallel::map_reduce_lines`] over the items of a slice.
+    pub fn map_reduce<I, T, M, R>(&self, items: &[I], map: M, reduce: R) -> Option<T>
+    where
+        I: Sync,
+        T: Send,
+        M: Fn(&I) -> T + Sync,
+        R: Fn(T, T) -> T + Sync,
+    {
+        let size = items.len().div_ceil(self.threads).max(self.min_chunk);
+        let chunks: Vec<_> = items.chunks(size).collect();
+        self.run(&chunks, |chunk| chunk.iter().map(&map).reduce(&reduce))
+            .into_iter()
+            .flatten()
+            .reduce(&reduce)
+    }
+
+    // one worker per chunk, results in chunk order
+    fn run<C, T, F>(&self, chunks: &[C], f: F) -> Vec<T>
+    where
+        C: Sync,
+        T: Send,
+        F: Fn(&C) -> T + Sync,
+    {
+        if self.threads == 1 || chunks.len() <= 1 {
+            return chunks.iter().map(f).collect();
+        }
+        let f = &f;
+        thread::scope(|scope| {
+            let workers: Vec<_> = chunks
+                .iter()
+                .map(|chunk| scope.spawn(move || f(chunk)))
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parallel::Parallel;
+    use crate::utils::{LineIterator, parse_u64};
+
+    fn numbers(count: u64) -> Vec<u8> {
+        (0..count)
+            .map(|n| format!("{n}\n"))
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    #[test]
+    fn test_chunks() {
+        let input = numbers(1000);
+        for threads in 1..10 {
+            let chunks = Parallel::with_threads(threads)
+                .min_chunk(1)
+                .line_chunks(&input);
+            assert!(chunks.len() <= threads);
+            assert_eq!(chunks.concat(), input);
+            assert!(chunks.iter().all(|chunk| chunk.ends_with(b"\n")));
+        }
+        assert!(Parallel::with_threads(4).line_chunks(b"").is_empty());
+        // too small to be split with the default cutoff
+        assert_eq!(
+            Parallel::with_threads(4).line_chunks(&input),
+            vec![&input[..]]
+        );
+        let items: Vec<u64> = (0..100).collect();
+        let parallel = Parallel::with_threads(4).min_chunk(30);
+        assert_eq!(parallel.map_reduce(&items, |_| 1, |a, b| a + b), Some(100));
+    }
+
+    #[test]
+    fn test_map_reduce_lines() {
+        let input = numbers(1000);
+        let serial: Vec<_> = LineIterator::new(&input).map(parse_u64).collect();
+        for threads in [1, 2, 3, 7, 16] {
+            let parallel = Parallel::with_threads(threads).min_chunk(1);
+            assert_eq!(parallel.map_lines(&input, parse_u64), serial);
+            let sum = parallel.map_reduce_lines(&input, parse_u64, |a, b| a + b);
+            assert_eq!(sum, Some(499500));
+            // not commutative, only passes if the chunks are combined in order
+            let joined =
+                parallel.map_reduce_lines(&input, |line| line.to_vec(), |a, b| [a, b].concat());
+            assert_eq!(
+                joined.unwrap(),
+                input
+                    .iter()
+                    .copied()
+                    .filter(|&b| b != b'\n')
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_map_reduce_slice() {
+        let items: Vec<u64> = (1..=100).collect();
+        for threads in [1, 4, 200] {
+            let parallel = Parallel::with_threads(threads).min_chunk(1);
+            assert_eq!(
+                parallel.map_reduce(&items, |x| x * x, |a, b| a + b),
+                Some(338350)
+            );
+        }
+        assert_eq!(
+            Parallel::new().map_reduce(&[] as &[u64], |x| *x, |a, b| a + b),
+            None
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// 2D point or vector. Follows grid conventions: `x` is the column and `y` the row,
+/// so `y` grows downward and [`Dir4::Up`] is `(0, -1)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point2 {
+    pub x: i64,
+    pub y: i64,
+}
+
+/// 3D point or vector.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+// component-wise arithmetic, same for both point types
+macro_rules! impl_ops {
+    ($point:ident { $($field:ident),+ }) => {
+        impl Add for $point {
+            type Output = Self;
+
+            #[inline(always)]
+            fn add(self, rhs: Self) -> Self::Output {
+                Self { $($field: self.$field + rhs.$field),+ }
+            }
+        }
+
+        impl Sub for $point {
+            type Output = Self;
+
+            #[inline(always)]
+            fn sub(self, rhs: Self) -> Self::Output {
+                Self { $($field: self.$field - rhs.$field),+ }
+            }
+        }
+
+        impl Mul<i64> for $point {
+            type Output = Self;
+
+            #[inline(always)]
+            fn mul(self, rhs: i64) -> Self::Output {
+                Self { $($field: self.$field * rhs),+ }
+            }
+        }
+
+        impl Neg for $point {
+            type Output = Self;
+
+            #[inline(always)]
+            fn neg(self) -> Self::Output {
+                Self { $($field: -self.$field),+ }
+            }
+        }
+
+        impl AddAssign for $point {
+            #[inline(always)]
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl SubAssign for $point {
+            #[inline(always)]
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl $point {
+            /// Sum of the absolute differences of each coordinate.
+            #[inline]
+            pub fn manhattan(self, other: Self) -> u64 {
+                0u64 $(+ self.$field.abs_diff(other.$field))+
+            }
+
+            /// Largest absolute difference of any coordinate (king moves).
+            #[inline]
+            pub fn chebyshev(self, other: Self) -> u64 {
+                0u64 $(.max(self.$field.abs_diff(other.$field)))+
+            }
+
+            /// Squared euclidean distance, exact so it can be compared and sorted.
+            #[inline]
+            pub fn dist_sq(self, other: Self) -> u64 {
+                0u64 $(+ self.$field.abs_diff(other.$field).pow(2))+
+            }
+        }
+    };
+}
+
+impl_ops!(Point2 { x, y });
+impl_ops!(Point3 { x, y, z });
+
+impl Point2 {
+    pub const ZERO: Self = Self::new(0, 0);
+
+    #[inline(always)]
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    /// Point of the grid cell `(row, col)`.
+    #[inline(always)]
+    pub const fn from_index(row: usize, col: usize) -> Self {
+        Self::new(col as i64, row as i64)
+    }
+
+    /// `(row, col)` of the point, `None` if a coordinate is negative.
+    /// The upper bound is checked by the grid itself.
+    #[inline(always)]
+    pub fn to_index(self) -> Option<(usize, usize)> {
+        Some((usize::try_from(self.y).ok()?, usize::try_from(self.x).ok()?))
+    }
+
+    /// Quarter turn clockwise around the origin (as seen on screen, `y` pointing down).
+    #[inline]
+    pub const fn rotate_cw(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    /// Quarter turn counter-clockwise around the origin.
+    #[inline]
+    pub const fn rotate_ccw(self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+}
+
+impl Point3 {
+    pub const ZERO: Self = Self::new(0, 0, 0);
+
+    #[inline(always)]
+    pub const fn new(x: i64, y: i64, z: i64) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Quarter turn around the x axis (right-hand rule).
+    #[inline]
+    pub const fn rotate_x(self) -> Self {
+        Self::new(self.x, -self.z, self.y)
+    }
+
+    /// Quarter turn around the y axis (right-hand rule).
+    #[inline]
+    pub const fn rotate_y(self) -> Self {
+        Self::new(self.z, self.y, -self.x)
+    }
+
+    /// Quarter turn around the z axis (right-hand rule).
+    #[inline]
+    pub const fn rotate_z(self) -> Self {
+        Self::new(-self.y, self.x, self.z)
+    }
+}
+
+/// Orthogonal directions, in clockwise order starting from `Up`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dir4 {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Dir4 {
+    pub const ALL: [Dir4; 4] = [Dir4::Up, Dir4::Right, Dir4::Down, Dir4::Left];
+
+    #[inline(always)]
+    pub const fn delta(self) -> Point2 {
+        match self {
+            Dir4::Up => Point2::new(0, -1),
+            Dir4::Right => Point2::new(1, 0),
+            Dir4::Down => Point2::new(0, 1),
+            Dir4::Left => Point2::new(-1, 0),
+        }
+    }
+
+    #[inline]
+    pub const fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    #[inline]
+    pub const fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    #[inline]
+    pub const fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+}
+
+impl TryFrom<u8> for Dir4 {
+    type Error = ();
+
+    fn try_from(c: u8) -> Result<Self, Self::Error> {
+        match c {
+            b'^' | b'U' => Ok(Dir4::Up),
+            b'>' | b'R' => Ok(Dir4::Right),
+            b'v' | b'D' => Ok(Dir4::Down),
+            b'<' | b'L' => Ok(Dir4::Left),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Orthogonal and diagonal directions, in clockwise order starting from `N` (up).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dir8 {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl Dir8 {
+    pub const ALL: [Dir8; 8] = [
+        Dir8::N,
+        Dir8::NE,
+        Dir8::E,
+        Dir8::SE,
+        Dir8::S,
+        Dir8::SW,
+        Dir8::W,
+        Dir8::NW,
+    ];
+
+    #[inline(always)]
+    pub const fn delta(self) -> Point2 {
+        match self {
+            Dir8::N => Point2::new(0, -1),
+            Dir8::NE => Point2::new(1, -1),
+            Dir8::E => Point2::new(1, 0),
+            Dir8::SE => Point2::new(1, 1),
+            Dir8::S => Point2::new(0, 1),
+            Dir8::SW => Point2::new(-1, 1),
+            Dir8::W => Point2::new(-1, 0),
+            Dir8::NW => Point2::new(-1, -1),
+        }
+    }
+
+    /// Eighth of a turn clockwise.
+    #[inline]
+    pub const fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 8]
+    }
+
+    /// Eighth of a turn counter-clockwise.
+    #[inline]
+    pub const fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 7) % 8]
+    }
+
+    #[inline]
+    pub const fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    #[inline]
+    fn from(dir: Dir4) -> Self {
+        Dir8::ALL[dir as usize * 2]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::point::{Dir4, Dir8, Point2, Point3};
+
+    #[test]
+    fn test_point2() {
+        let a = Point2::new(1, -2);
+        let b = Point2::new(-3, 4);
+        assert_eq!(a + b, Point2::new(-2, 2));
+        assert_eq!(a - b, Point2::new(4, -6));
+        assert_eq!(-a * 2, Point2::new(-2, 4));
+        assert_eq!(a.manhattan(b), 10);
+        assert_eq!(a.chebyshev(b), 6);
+        assert_eq!(a.dist_sq(b), 52);
+        assert_eq!(Point2::from_index(2, 3).to_index(), Some((2, 3)));
+        assert_eq!(Point2::new(3, -1).to_index(), None);
+    }
+
+    #[test]
+    fn test_point3() {
+        let p = Point3::new(1, 2, 3);
+        assert_eq!(p.manhattan(Point3::ZERO), 6);
+        assert_eq!(p.dist_sq(Point3::ZERO), 14);
+        assert_eq!(p.rotate_x().rotate_x().rotate_x().rotate_x(), p);
+        assert_eq!(p.rotate_y().rotate_y(), Point3::new(-1, 2, -3));
+        assert_eq!(p.rotate_z(), Point3::new(-2, 1, 3));
+    }
+
+    #[test]
+    fn test_dirs() {
+        for dir in Dir4::ALL {
+            assert_eq!(dir.delta().rotate_cw(), dir.turn_right().delta());
+            assert_eq!(dir.delta().rotate_ccw(), dir.turn_left().delta());
+            assert_eq!(-dir.delta(), dir.opposite().delta());
+            assert_eq!(Dir8::from(dir).delta(), dir.delta());
+        }
+        for dir in Dir8::ALL {
+            assert_eq!(dir.turn_right().turn_left(), dir);
+            assert_eq!(-dir.delta(), dir.opposite().delta());
+        }
+        assert_eq!(Dir4::try_from(b'<'), Ok(Dir4::Left));
+    }
+}
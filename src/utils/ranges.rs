@@ -0,0 +1,92 @@
+use std::ops::RangeInclusive;
+
+/// Integers the range helpers work on.
+pub trait Bound: Copy + Ord {
+    /// The next value, `None` for the largest one.
+    fn successor(self) -> Option<Self>;
+}
+
+macro_rules! impl_bound {
+    ($($int:ty),+) => {
+        $(impl Bound for $int {
+            #[inline(always)]
+            fn successor(self) -> Option<Self> {
+                self.checked_add(1)
+            }
+        })+
+    };
+}
+
+impl_bound!(i32, i64, u32, u64, u128, usize);
+
+/// Sorted copy of `ranges` where overlapping or adjacent ranges are merged, so every
+/// value covered by the input is in exactly one range of the output.
+pub fn merge<T: Bound>(ranges: &[RangeInclusive<T>]) -> Vec<RangeInclusive<T>> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_unstable_by_key(|range| *range.start());
+    let mut merged: Vec<RangeInclusive<T>> = Vec::with_capacity(sorted.len());
+    for range in sorted {
+        match merged.last_mut() {
+            // a range ending on the largest value is followed by nothing
+            Some(last)
+                if last
+                    .end()
+                    .successor()
+                    .is_none_or(|next| *range.start() <= next) =>
+            {
+                if range.end() > last.end() {
+                    *last = *last.start()..=*range.end();
+                }
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Pairs of indices `(i, j)` with `i < j` of the ranges sharing at least one value,
+/// sorted. Ranges that only touch end to start don't overlap.
+pub fn overlaps<T: Bound>(ranges: &[RangeInclusive<T>]) -> Vec<(usize, usize)> {
+    let mut order: Vec<_> = (0..ranges.len()).collect();
+    order.sort_unstable_by_key(|&i| *ranges[i].start());
+    let mut pairs = Vec::new();
+    for (pos, &i) in order.iter().enumerate() {
+        // every later range starting before this one ends overlaps it
+        for &j in order[pos + 1..]
+            .iter()
+            .take_while(|&&j| ranges[j].start() <= ranges[i].end())
+        {
+            pairs.push((i.min(j), i.max(j)));
+        }
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::ranges::{merge, overlaps};
+
+    #[test]
+    fn test_merge() {
+        let ranges = [
+            10..=14,
+            3..=5,
+            16..=20,
+            12..=18,
+            6..=6,
+            30..=u64::MAX,
+            25..=40,
+        ];
+        assert_eq!(merge(&ranges), vec![3..=6, 10..=20, 25..=u64::MAX]);
+        assert!(merge::<u64>(&[]).is_empty());
+        assert_eq!(merge(&[5..=u128::MAX, 0..=4]), vec![0..=u128::MAX]);
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let ranges = [10..=14, 3..=5, 16..=20, 12..=18, 6..=6, 14..=14];
+        assert_eq!(overlaps(&ranges), vec![(0, 3), (0, 5), (2, 3), (3, 5)]);
+        assert!(overlaps(&[1..=2, 3..=4]).is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+use std::ops::Range;
+
+/// Values tagged with a position in `0..len`, answering "how many values `<= x` have
+/// their position in `range`" in O(log n) for any range.
+/// It's a persistent segment tree over the sorted values with one version per prefix
+/// of positions, so a range is just the difference between two versions.
+#[derive(Clone, Debug)]
+pub struct PrefixRank {
+    values: Vec<u64>,
+    nodes: Vec<Node>,
+    // roots[k]: tree holding the values of positions `0..k`
+    roots: Vec<u32>,
+}
+
+// node 0 is the shared empty tree, its children point back to itself
+#[derive(Clone, Copy, Debug, Default)]
+struct Node {
+    left: u32,
+    right: u32,
+    count: u32,
+}
+
+impl PrefixRank {
+    /// `entries` are `(position, value)` pairs, any position can have zero or several values.
+    pub fn new(len: usize, entries: impl IntoIterator<Item = (usize, u64)>) -> Self {
+        let mut entries: Vec<_> = entries.into_iter().collect();
+        entries.sort_unstable();
+        assert!(
+            entries.last().is_none_or(|&(pos, _)| pos < len),
+            "position out of range"
+        );
+        let mut values: Vec<_> = entries.iter().map(|&(_, value)| value).collect();
+        values.sort_unstable();
+        values.dedup();
+
+        let mut rank = Self {
+            values,
+            nodes: vec![Node::default()],
+            roots: Vec::with_capacity(len + 1),
+        };
+        let mut root = 0;
+        let mut entries = entries.into_iter().peekable();
+        for pos in 0..len {
+            rank.roots.push(root);
+            while let Some((_, value)) = entries.next_if(|&(p, _)| p == pos) {
+                let slot = rank.values.binary_search(&value).unwrap();
+                root = rank.insert(root, 0..rank.values.len(), slot);
+            }
+        }
+        rank.roots.push(root);
+        rank
+    }
+
+    /// Number of positions, the `len` given to [`PrefixRank::new`].
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.roots.len() - 1
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of values `<= value` tagged with a position in `range`.
+    pub fn count_le(&self, range: Range<usize>, value: u64) -> u64 {
+        assert!(range.end <= self.len(), "range out of bounds");
+        if range.is_empty() {
+            return 0;
+        }
+        let slots = self.values.partition_point(|&v| v <= value);
+        let below = |root| self.count_below(root, 0..self.values.len(), slots);
+        (below(self.roots[range.end]) - below(self.roots[range.start])) as u64
+    }
+
+    /// Number of values `< value` tagged with a position in `range`.
+    pub fn count_lt(&self, range: Range<usize>, value: u64) -> u64 {
+        match value.checked_sub(1) {
+            Some(value) => self.count_le(range, value),
+            None => 0,
+        }
+    }
+
+    // copy of the path from `node` to `slot` with one more value in `slot`
+    fn insert(&mut self, node: u32, span: Range<usize>, slot: usize) -> u32 {
+        let mut copy = self.nodes[node as usize];
+        copy.count += 1;
+        if span.len() > 1 {
+            let mid = span.start + span.len() / 2;
+            if slot < mid {
+                copy.left = self.insert(copy.left, span.start..mid, slot);
+            } else {
+                copy.right = self.insert(copy.right, mid..span.end, slot);
+            }
+        }
+        self.nodes.push(copy);
+        (self.nodes.len() - 1) as u32
+    }
+
+    // values in the slots `0..slots` of the tree at `node`
+    fn count_below(&self, node: u32, span: Range<usize>, slots: usize) -> u32 {
+        let current = self.nodes[node as usize];
+        if node == 0 || slots <= span.start {
+            return 0;
+        }
+        if slots >= span.end {
+            return current.count;
+        }
+        let mid = span.start + span.len() / 2;
+        self.count_below(current.left, span.start..mid, slots)
+            + self.count_below(current.right, mid..span.end, slots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::rank::PrefixRank;
+    use crate::utils::rng::XorShift64;
+
+    #[test]
+    fn test_count() {
+        let mut rng = XorShift64::new(0x5eed);
+        for _ in 0..50 {
+            let len = rng.below(40) as usize;
+            let entries: Vec<_> = (0..rng.below(80))
+                .filter(|_| len > 0)
+                .map(|_| (rng.below(len as u64) as usize, rng.below(20)))
+                .collect();
+            let rank = PrefixRank::new(len, entries.iter().copied());
+            assert_eq!(rank.len(), len);
+            for _ in 0..50 {
+                let start = rng.range(0..=len as u64) as usize;
+                let end = rng.range(start as u64..=len as u64) as usize;
+                let value = rng.below(22);
+                let expected = |limit: u64| {
+                    entries
+                        .iter()
+                        .filter(|&&(pos, v)| (start..end).contains(&pos) && v < limit)
+                        .count() as u64
+                };
+                assert_eq!(rank.count_le(start..end, value), expected(value + 1));
+                assert_eq!(rank.count_lt(start..end, value), expected(value));
+            }
+        }
+        assert_eq!(PrefixRank::new(3, []).count_le(0..3, u64::MAX), 0);
+    }
+}
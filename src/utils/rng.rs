@@ -0,0 +1,65 @@
+use std::ops::RangeInclusive;
+
+/// Xorshift generator for property tests and generated inputs, fast and reproducible
+/// from a seed but obviously not for anything that needs real randomness.
+#[derive(Clone, Debug)]
+pub struct XorShift64(u64);
+
+impl XorShift64 {
+    pub fn new(seed: u64) -> Self {
+        // the all-zero state never leaves zero
+        Self(seed.max(1))
+    }
+
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    /// Value in `0..n`, slightly biased for huge `n` which doesn't matter here.
+    #[inline]
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "empty range");
+        self.next_u64() % n
+    }
+
+    #[inline]
+    pub fn range(&mut self, range: RangeInclusive<u64>) -> u64 {
+        let (start, end) = range.into_inner();
+        match (end - start).checked_add(1) {
+            Some(len) => start + self.below(len),
+            None => self.next_u64(),
+        }
+    }
+
+    #[inline]
+    pub fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::rng::XorShift64;
+
+    #[test]
+    fn test_range() {
+        let mut rng = XorShift64::new(0);
+        let mut seen = [false; 6];
+        for _ in 0..1000 {
+            let n = rng.range(5..=10);
+            assert!((5..=10).contains(&n));
+            seen[(n - 5) as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+        assert_eq!(
+            XorShift64::new(42).next_u64(),
+            XorShift64::new(42).next_u64()
+        );
+    }
+}
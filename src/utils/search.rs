@@ -0,0 +1,358 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+use crate::utils::grid::Grid;
+
+/// Anything that can list the outgoing edges of a node, all the searches below work on it.
+pub trait Graph {
+    type Node: Copy + Eq + Hash;
+
+    /// Outgoing edges of `node` as `(neighbor, cost)`. BFS ignores the cost.
+    fn edges(&self, node: Self::Node) -> impl Iterator<Item = (Self::Node, u64)>;
+}
+
+/// Path found by a search, `nodes` goes from the start to the goal (both included).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path<N> {
+    pub nodes: Vec<N>,
+    pub cost: u64,
+}
+
+/// Directed graph over `0..len` nodes stored as adjacency lists.
+#[derive(Clone, Debug, Default)]
+pub struct AdjacencyList {
+    edges: Vec<Vec<(usize, u64)>>,
+}
+
+impl AdjacencyList {
+    pub fn new(len: usize) -> Self {
+        Self {
+            edges: vec![Vec::new(); len],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize, cost: u64) {
+        self.edges[from].push((to, cost));
+    }
+}
+
+impl Graph for AdjacencyList {
+    type Node = usize;
+
+    #[inline]
+    fn edges(&self, node: usize) -> impl Iterator<Item = (usize, u64)> {
+        self.edges[node].iter().copied()
+    }
+}
+
+/// Grid seen as a graph between `(row, col)` cells. `cost` receives the cell we leave and
+/// the cell we enter and returns `None` when the move isn't allowed (walls).
+pub struct GridGraph<'a, T, F> {
+    grid: &'a Grid<T>,
+    cost: F,
+    diagonals: bool,
+}
+
+impl<'a, T, F> GridGraph<'a, T, F>
+where
+    F: Fn(&T, &T) -> Option<u64>,
+{
+    pub fn new(grid: &'a Grid<T>, cost: F) -> Self {
+        Self {
+            grid,
+            cost,
+            diagonals: false,
+        }
+    }
+
+    /// Also allow diagonal moves.
+    pub fn diagonals(mut self) -> Self {
+        self.diagonals = true;
+        self
+    }
+}
+
+impl<T, F> Graph for GridGraph<'_, T, F>
+where
+    F: Fn(&T, &T) -> Option<u64>,
+{
+    type Node = (usize, usize);
+
+    fn edges(&self, (row, col): (usize, usize)) -> impl Iterator<Item = ((usize, usize), u64)> {
+        // only one of the two is `Some`, the chain keeps a single iterator type
+        let four = (!self.diagonals).then(|| self.grid.neighbors4(row, col));
+        let eight = self.diagonals.then(|| self.grid.neighbors8(row, col));
+        let from = &self.grid[(row, col)];
+        let neighbors = four
+            .into_iter()
+            .flatten()
+            .chain(eight.into_iter().flatten());
+        neighbors.filter_map(move |next| {
+            let cost = (self.cost)(from, &self.grid[next])?;
+            Some((next, cost))
+        })
+    }
+}
+
+fn reconstruct<N: Copy + Eq + Hash>(parents: &HashMap<N, N>, goal: N, cost: u64) -> Path<N> {
+    let mut nodes = vec![goal];
+    let mut node = goal;
+    while let Some(&parent) = parents.get(&node) {
+        nodes.push(parent);
+        node = parent;
+    }
+    nodes.reverse();
+    Path { nodes, cost }
+}
+
+/// Shortest path in number of edges from `start` to the first node accepted by `is_goal`.
+pub fn bfs<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    mut is_goal: impl FnMut(G::Node) -> bool,
+) -> Option<Path<G::Node>> {
+    let mut parents = HashMap::new();
+    let mut dist = HashMap::from([(start, 0)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        let d = dist[&node];
+        if is_goal(node) {
+            return Some(reconstruct(&parents, node, d));
+        }
+        for (next, _) in graph.edges(node) {
+            if let Entry::Vacant(entry) = dist.entry(next) {
+                entry.insert(d + 1);
+                parents.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Number of edges from `start` to every reachable node.
+pub fn bfs_distances<G: Graph>(graph: &G, start: G::Node) -> HashMap<G::Node, u64> {
+    let mut dist = HashMap::from([(start, 0)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        let d = dist[&node];
+        for (next, _) in graph.edges(node) {
+            dist.entry(next).or_insert_with(|| {
+                queue.push_back(next);
+                d + 1
+            });
+        }
+    }
+    dist
+}
+
+// min-heap entry, only the priority is compared so nodes don't need `Ord`
+struct HeapEntry<N> {
+    priority: u64,
+    cost: u64,
+    node: N,
+}
+
+impl<N> PartialEq for HeapEntry<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<N> Eq for HeapEntry<N> {}
+
+impl<N> PartialOrd for HeapEntry<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> Ord for HeapEntry<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+/// Cheapest path from `start` to the first node accepted by `is_goal`.
+pub fn dijkstra<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    is_goal: impl FnMut(G::Node) -> bool,
+) -> Option<Path<G::Node>> {
+    astar(graph, start, is_goal, |_| 0)
+}
+
+/// Dijkstra guided by `heuristic`, which must never overestimate the remaining cost
+/// for the returned path to be the cheapest.
+pub fn astar<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    mut is_goal: impl FnMut(G::Node) -> bool,
+    mut heuristic: impl FnMut(G::Node) -> u64,
+) -> Option<Path<G::Node>> {
+    let mut parents = HashMap::new();
+    let mut best = HashMap::from([(start, 0)]);
+    let mut heap = BinaryHeap::from([HeapEntry {
+        priority: heuristic(start),
+        cost: 0,
+        node: start,
+    }]);
+    while let Some(HeapEntry { cost, node, .. }) = heap.pop() {
+        if best[&node] < cost {
+            // stale entry, a cheaper way was found after it was pushed
+            continue;
+        }
+        if is_goal(node) {
+            return Some(reconstruct(&parents, node, cost));
+        }
+        for (next, edge) in graph.edges(node) {
+            let next_cost = cost + edge;
+            if best.get(&next).is_none_or(|&c| next_cost < c) {
+                best.insert(next, next_cost);
+                parents.insert(next, node);
+                heap.push(HeapEntry {
+                    priority: next_cost + heuristic(next),
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Nodes reachable from `starts` ordered so every edge goes forward, `None` if there's a cycle.
+pub fn topological_order<G: Graph>(
+    graph: &G,
+    starts: impl IntoIterator<Item = G::Node>,
+) -> Option<Vec<G::Node>> {
+    // false while the node is on the DFS stack, true once all its successors are done
+    let mut done = HashMap::new();
+    let mut order = Vec::new();
+    for start in starts {
+        if done.contains_key(&start) {
+            continue;
+        }
+        done.insert(start, false);
+        let mut stack = vec![(start, graph.edges(start))];
+        while let Some((node, edges)) = stack.last_mut() {
+            let node = *node;
+            match edges.next() {
+                Some((next, _)) => match done.get(&next) {
+                    Some(false) => return None,
+                    Some(true) => {}
+                    None => {
+                        done.insert(next, false);
+                        stack.push((next, graph.edges(next)));
+                    }
+                },
+                None => {
+                    done.insert(node, true);
+                    order.push(node);
+                    stack.pop();
+                }
+            }
+        }
+    }
+    order.reverse();
+    Some(order)
+}
+
+/// Number of distinct paths from `start` to nodes accepted by `is_end` in a DAG,
+/// `None` if a cycle is reachable from `start`.
+pub fn count_paths<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    mut is_end: impl FnMut(G::Node) -> bool,
+) -> Option<u64> {
+    let order = topological_order(graph, [start])?;
+    let mut ways = HashMap::from([(start, 1u64)]);
+    let mut total = 0;
+    for node in order {
+        let count = ways[&node];
+        if is_end(node) {
+            total += count;
+        }
+        for (next, _) in graph.edges(node) {
+            *ways.entry(next).or_default() += count;
+        }
+    }
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::grid::Grid;
+    use crate::utils::search::{
+        AdjacencyList, GridGraph, astar, bfs, bfs_distances, count_paths, dijkstra,
+        topological_order,
+    };
+
+    const MAZE: &[u8] = b"S.#.\n..#E\n#...\n";
+
+    fn walls(_: &u8, to: &u8) -> Option<u64> {
+        (*to != b'#').then_some(1)
+    }
+
+    #[test]
+    fn test_grid_bfs() {
+        let grid = Grid::from_ascii(MAZE);
+        let graph = GridGraph::new(&grid, walls);
+        let goal = grid.find(b'E').unwrap();
+        let path = bfs(&graph, (0, 0), |node| node == goal).unwrap();
+        assert_eq!(path.cost, 6);
+        assert_eq!(path.nodes.len(), 7);
+        assert_eq!(path.nodes.first(), Some(&(0, 0)));
+        assert_eq!(path.nodes.last(), Some(&goal));
+        assert_eq!(bfs_distances(&graph, (0, 0))[&(0, 3)], 7);
+
+        let diagonal = GridGraph::new(&grid, walls).diagonals();
+        assert_eq!(bfs(&diagonal, (0, 0), |node| node == goal).unwrap().cost, 3);
+    }
+
+    #[test]
+    fn test_weighted() {
+        let mut graph = AdjacencyList::new(4);
+        graph.add_edge(0, 1, 1);
+        graph.add_edge(0, 2, 5);
+        graph.add_edge(1, 2, 1);
+        graph.add_edge(2, 3, 1);
+        graph.add_edge(1, 3, 10);
+        let path = dijkstra(&graph, 0, |node| node == 3).unwrap();
+        assert_eq!(path.nodes, vec![0, 1, 2, 3]);
+        assert_eq!(path.cost, 3);
+        let heuristic = |node: usize| [2, 1, 1, 0][node];
+        assert_eq!(astar(&graph, 0, |node| node == 3, heuristic), Some(path));
+        assert_eq!(dijkstra(&graph, 3, |node| node == 0), None);
+    }
+
+    #[test]
+    fn test_dag() {
+        let mut graph = AdjacencyList::new(5);
+        for (from, to) in [(0, 1), (0, 2), (1, 3), (2, 3), (3, 4), (1, 4)] {
+            graph.add_edge(from, to, 1);
+        }
+        let order = topological_order(&graph, [0]).unwrap();
+        let rank = |node| order.iter().position(|&n| n == node).unwrap();
+        assert!((0..5).all(|from| {
+            graph.edges[from]
+                .iter()
+                .all(|&(to, _)| rank(from) < rank(to))
+        }));
+        assert_eq!(count_paths(&graph, 0, |node| node == 4), Some(3));
+
+        graph.add_edge(4, 1, 1);
+        assert_eq!(topological_order(&graph, [0]), None);
+        assert_eq!(count_paths(&graph, 0, |node| node == 4), None);
+    }
+}